@@ -22,7 +22,7 @@ use std::time::{self, Duration, Instant};
 use minifb::{Key, KeyRepeat, WindowOptions, Window, Scale, MouseMode, MouseButton};
 use line_drawing::{BresenhamCircle, Midpoint};
 use lean::Vec2;
-use lean::library::Renderer;
+use lean::Renderer;
 
 
 // Modules --------------------------------------------------------------------
@@ -30,6 +30,22 @@ mod stick_example;
 use self::stick_example::Example;
 
 
+// Statics --------------------------------------------------------------------
+// 3x5 pixel glyphs, one bit per pixel in rows from the top left
+const FONT_LETTERS: [u16; 26] = [
+    0x2bed, 0x6bae, 0x3923, 0x6b6e, 0x79a7, 0x79a4,
+    0x396b, 0x5bed, 0x7497, 0x126a, 0x5bad, 0x4927,
+    0x5fed, 0x6b6d, 0x2b6a, 0x6ba4, 0x2b73, 0x6bad,
+    0x388e, 0x7492, 0x5b6f, 0x5b6a, 0x5bfd, 0x5aad,
+    0x5a92, 0x72a7
+];
+
+const FONT_DIGITS: [u16; 10] = [
+    0x7b6f, 0x2c97, 0x62a7, 0x628e, 0x5bc9, 0x798e,
+    0x39ef, 0x7292, 0x7bef, 0x7bce
+];
+
+
 // Main -----------------------------------------------------------------------
 fn main() {
 
@@ -88,6 +104,7 @@ fn main() {
             window.is_key_down(Key::Enter),
             window.is_key_down(Key::R),
             window.is_key_down(Key::P),
+            window.is_key_pressed(Key::X, KeyRepeat::No),
             window.is_key_pressed(Key::B, KeyRepeat::No)
         );
 
@@ -150,6 +167,40 @@ impl Context {
         }
     }
 
+    // Draws text centered around the given position
+    pub fn text(&mut self, x: f32, y: f32, text: &str, color: u32) {
+        let width = text.chars().count() as i32 * 4 - 1;
+        let left = (x * self.scale) as i32 - width / 2;
+        let top = (y * self.scale) as i32 - 2;
+        for (i, c) in text.chars().enumerate() {
+            let glyph = if c.is_ascii_alphabetic() {
+                FONT_LETTERS[(c.to_ascii_uppercase() as u8 - b'A') as usize]
+
+            } else if c.is_ascii_digit() {
+                FONT_DIGITS[(c as u8 - b'0') as usize]
+
+            } else if c == '.' {
+                0x0002
+
+            } else if c == '-' {
+                0x01c0
+
+            } else {
+                0
+            };
+
+            for bit in 0..15 {
+                if glyph & (1 << (14 - bit)) != 0 {
+                    let x = left + i as i32 * 4 + bit % 3;
+                    let y = top + bit / 3;
+                    if x > 0 && x < self.width as i32 && y > 0 && y < self.height as i32 {
+                        self.buffer[y as usize * self.width + x as usize] = color;
+                    }
+                }
+            }
+        }
+    }
+
     pub fn line_vec(&mut self, start: Vec2, end: Vec2, color: u32) {
         self.line(start.x, start.y, end.x, end.y, color);
     }
@@ -213,6 +264,10 @@ impl Renderer for Context {
 
     }
 
+    fn draw_text(&mut self, p: Vec2, text: &str, color: u32) {
+        self.text(p.x, p.y, text, color);
+    }
+
 }
 
 
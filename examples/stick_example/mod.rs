@@ -8,12 +8,21 @@
 
 
 // External Dependencies ------------------------------------------------------
-use lean::Vec2;
+use lean::{DebugDrawOptions, SkeletalData, Renderer, Vec2};
 use lean::library::{
-    Collider, StickFigure, StickFigureConfig, Scarf, Weapon, Renderer
+    Collider, StickFigure, StickFigureConfig, StickFigureLimb, Scarf, Weapon
 };
 
 
+// Statics --------------------------------------------------------------------
+const SEVER_ORDER: [(&str, Option<f32>); 4] = [
+    ("L.Hand", Some(0.5)),
+    ("Head", None),
+    ("R.Leg", Some(0.4)),
+    ("Back", None)
+];
+
+
 // Internal Dependencies ------------------------------------------------------
 use super::Context;
 
@@ -26,13 +35,30 @@ use self::player::{Player, PlayerState};
 // Example Code ---------------------------------------------------------------
 pub struct Level {
     pub width: f32,
-    pub floor: f32
+    pub floor: f32,
+    pub hill: (f32, f32, f32)
 }
 
 impl Level {
 
+    fn floor_at(&self, x: f32) -> f32 {
+        let (center, width, height) = self.hill;
+        let d = (x - center).abs() / (width * 0.5);
+        self.floor - height * (1.0 - d).max(0.0)
+    }
+
     fn draw(&mut self, context: &mut Context) {
-        context.line(0.0, self.floor + 1.0, self.width, self.floor + 1.0, 0x00c0_c0c0);
+        let (center, width, _) = self.hill;
+        let points = [
+            0.0,
+            center - width * 0.5,
+            center,
+            center + width * 0.5,
+            self.width
+        ];
+        for x in points.windows(2) {
+            context.line(x[0], self.floor_at(x[0]) + 1.0, x[1], self.floor_at(x[1]) + 1.0, 0x00c0_c0c0);
+        }
     }
 
 }
@@ -40,7 +66,7 @@ impl Level {
 impl Collider for Level {
 
     fn world(&self, mut p: Vec2) -> Option<(Vec2, i32, i32)> {
-        let floor = self.floor;
+        let floor = self.floor_at(p.x);
         let (mut horizontal, mut vertical) = (0, 0);
 
         if p.y > floor {
@@ -70,9 +96,12 @@ impl Collider for Level {
 pub struct Example {
     player: Player,
     figure: StickFigure<PlayerState, Context, Level>,
+    limbs: Vec<StickFigureLimb>,
     level: Level,
-    show_bounds: bool,
-    input_direction: f32
+    gravity: Vec2,
+    show_debug: bool,
+    input_direction: f32,
+    aim_target: Option<Vec2>
 }
 
 impl Example {
@@ -95,9 +124,18 @@ impl Example {
             shoulder_height: 25.0,
             line_of_sight_length: 80.0,
 
-            leanback_min: -45.0,
-            leanback_max: 35.0,
+            look_at_back_limits: (-0.405, 0.315),
+            look_at_head_limits: (-0.587, 0.457),
             leanback_head_factor: 1.45,
+            aim_turn_speed: 12.0,
+            ik_blend_duration: 0.1,
+            ik_swing_duration: 0.15,
+            ik_max_stretch: 1.1,
+            foot_probe_height: 20.0,
+            hip_drop_max: 8.0,
+            foot_lock_speed: 150.0,
+            foot_lock_distance: 16.0,
+            foot_lock_release: 0.1,
 
             // TODO move to weapon
             recoil_leanback_factor: 2.0,
@@ -116,24 +154,34 @@ impl Example {
 
             crouching_factor: 0.5,
             crouch_compression: 3.0,
-            crouch_speed: 1.0
+            crouch_speed: 1.0,
+
+            get_up_duration: 0.5
 
         };
 
+        let gravity = Vec2::new(0.0, config.fall_limit * 100.0);
         let player = Player::new(config.clone());
-        let mut figure = StickFigure::default(player.get_state(), config);
+        // Same as the default figure, but with limited joints when split
+        // into a ragdoll
+        let skeleton = SkeletalData::from_ron(include_str!("figure.ron")).expect("Invalid figure skeleton");
+        let mut figure = StickFigure::from_skeleton(skeleton, player.get_state(), config);
         figure.add_accessory("Scarf", "Back", Scarf::new(24.0, 6, 0x00ff_ff00));
         figure.add_accessory("Weapon", "Back", Weapon::default(0x00ff_ff00));
 
         Self {
             player: player,
             figure: figure,
+            limbs: Vec::new(),
             level: Level {
                 width,
-                floor: height * 0.75
+                floor: height * 0.75,
+                hill: (width * 0.7, 160.0, 24.0)
             },
-            show_bounds: false,
-            input_direction: 0.0
+            gravity: gravity,
+            show_debug: false,
+            input_direction: 0.0,
+            aim_target: None
         }
 
     }
@@ -150,11 +198,13 @@ impl Example {
         reset: bool,
         release: bool,
         pickup: bool,
-        bounds: bool
+        sever: bool,
+        debug: bool
     ) {
 
         if let Some((x, y)) = mouse_pos {
             self.input_direction = self.player.compute_view_angle(Vec2::new(x, y));
+            self.aim_target = Some(Vec2::new(x, y));
         }
 
         if kill {
@@ -172,8 +222,15 @@ impl Example {
             self.figure.attach("Weapon");
         }
 
-        if bounds {
-            self.show_bounds = !self.show_bounds;
+        if sever {
+            let figure = &mut self.figure;
+            if let Some(limb) = SEVER_ORDER.iter().filter_map(|&(bone, at)| figure.sever(bone, at)).next() {
+                self.limbs.push(limb);
+            }
+        }
+
+        if debug {
+            self.show_debug = !self.show_debug;
         }
 
         self.player.update_server(fire);
@@ -184,12 +241,17 @@ impl Example {
     pub fn draw(&mut self, context: &mut Context) {
 
         self.figure.set_state(self.player.get_state());
+        self.figure.set_aim_target(self.aim_target);
         self.figure.draw(context, &self.level);
         self.level.draw(context);
 
-        if self.show_bounds {
-            let b = self.figure.world_bounds();
-            context.draw_rect(b.0, b.1, if self.figure.skeleton().at_rest() { 0x0000_c0f0 } else { 0x00ff_0000 });
+        for limb in &mut self.limbs {
+            limb.step(context.dt(), self.gravity, &self.level);
+            limb.draw(context);
+        }
+
+        if self.show_debug {
+            self.figure.skeleton().debug_draw(context, DebugDrawOptions::default());
         }
 
     }
@@ -8,21 +8,39 @@
 
 
 // STD Dependencies -----------------------------------------------------------
+use std::path::Path;
+use std::sync::Arc;
 use std::collections::HashMap;
 
 
+// Internal Dependencies ------------------------------------------------------
+use super::data::{self, DataError};
+
+
 // Types ----------------------------------------------------------------------
-pub type AnimationFrameBone = (&'static str, f32);
+pub type AnimationFrameBone = (String, f32);
 type AnimationFrame = (f32, Vec<AnimationFrameBone>);
 
 
 // Animation Data Abstraction -------------------------------------------------
-#[derive(Debug)]
+#[derive(Debug, Deserialize)]
 pub struct AnimationData {
     pub duration: f32,
     pub key_frames: Vec<AnimationFrame>
 }
 
+impl AnimationData {
+
+    pub fn from_ron(text: &str) -> Result<Arc<Self>, DataError> {
+        data::from_ron(text)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Arc<Self>, DataError> {
+        data::load_ron(path)
+    }
+
+}
+
 
 // Animator State Machine Abstraction -----------------------------------------
 #[derive(Debug, Default)]
@@ -83,7 +101,7 @@ impl AnimatorState {
         }
     }
 
-    pub fn add_animation(&mut self, data: &'static AnimationData) {
+    pub fn add_animation(&mut self, data: Arc<AnimationData>) {
         self.animations.push(AnimationInstance::new(data, 1.0));
     }
 
@@ -204,12 +222,12 @@ pub struct AnimationInstance {
     blend: f32,
     speed: f32,
     key_index: usize,
-    data: &'static AnimationData
+    data: Arc<AnimationData>
 }
 
 impl AnimationInstance {
 
-    fn new(data: &'static AnimationData, speed: f32) -> Self {
+    fn new(data: Arc<AnimationData>, speed: f32) -> Self {
         Self {
             time: 0.0,
             blend: 0.0,
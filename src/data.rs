@@ -0,0 +1,127 @@
+// Copyright (c) 2017 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// STD Dependencies -----------------------------------------------------------
+use std::fmt;
+use std::io;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use std::error::Error;
+
+
+// External Dependencies ------------------------------------------------------
+use serde::de::DeserializeOwned;
+use ron;
+
+
+// Data Loading Errors --------------------------------------------------------
+#[derive(Debug)]
+pub enum DataError {
+    Io(io::Error),
+    Parse(String)
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DataError::Io(ref err) => write!(f, "Failed to read data file: {}", err),
+            DataError::Parse(ref err) => write!(f, "Failed to parse data: {}", err)
+        }
+    }
+}
+
+impl Error for DataError {}
+
+impl From<io::Error> for DataError {
+    fn from(err: io::Error) -> Self {
+        DataError::Io(err)
+    }
+}
+
+
+// RON Helpers ----------------------------------------------------------------
+pub fn from_ron<T: DeserializeOwned>(text: &str) -> Result<Arc<T>, DataError> {
+    ron::de::from_str(text).map(Arc::new).map_err(|err| {
+        DataError::Parse(err.to_string())
+    })
+}
+
+pub fn load_ron<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<Arc<T>, DataError> {
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+    from_ron(&text)
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod test {
+
+    use super::DataError;
+    use super::super::{AnimationData, SkeletalData, Skeleton};
+
+    // The ```ron blocks of the README, in order
+    fn readme_examples() -> Vec<String> {
+        include_str!("../README.md").split("```ron").skip(1).map(|block| {
+            block.split("```").next().unwrap().to_string()
+
+        }).collect()
+    }
+
+    #[test]
+    fn test_readme_skeleton() {
+        let data = SkeletalData::from_ron(&readme_examples()[0]).unwrap();
+        assert_eq!(data.bones.len(), 4);
+        assert_eq!(data.bones[3].0, "L.Hand");
+        assert_eq!((data.bones[3].1).4, Some(-2.985));
+        assert_eq!(data.ragdoll_parents.len(), 1);
+        assert_eq!(data.ragdoll_splits.len(), 1);
+        assert_eq!(data.sockets.len(), 1);
+        assert_eq!(data.hitboxes.len(), 2);
+        assert_eq!(data.constraints.len(), 2);
+        assert!(Skeleton::try_new(data).is_ok());
+    }
+
+    #[test]
+    fn test_readme_animation() {
+        let data = AnimationData::from_ron(&readme_examples()[1]).unwrap();
+        assert_eq!(data.duration, 1.0);
+        assert_eq!(data.key_frames.len(), 2);
+        assert_eq!(data.key_frames[1].0, 0.5);
+        assert_eq!((data.key_frames[1].1)[1], ("L.Hand".to_string(), -0.5));
+    }
+
+    #[test]
+    fn test_optional_skeleton_fields() {
+        let data = SkeletalData::from_ron(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+        ])"#).unwrap();
+        assert_eq!(data.bones.len(), 1);
+        assert!(data.ragdoll_parents.is_empty());
+        assert!(data.ragdoll_splits.is_empty());
+        assert!(data.constraints.is_empty());
+        assert!(data.sockets.is_empty());
+        assert!(data.hitboxes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        match SkeletalData::from_ron("(bones: [(\"Root\")])") {
+            Err(DataError::Parse(_)) => {},
+            _ => panic!("expected a parse error")
+        }
+        match AnimationData::load("does/not/exist.ron") {
+            Err(DataError::Io(_)) => {},
+            _ => panic!("expected an io error")
+        }
+    }
+
+}
@@ -12,6 +12,10 @@
 extern crate lazy_static;
 #[macro_use]
 extern crate downcast_rs;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde;
+extern crate ron;
 
 
 // Enums ----------------------------------------------------------------------
@@ -27,6 +31,9 @@ pub enum Space {
 mod util;
 pub use self::util::*;
 
+mod data;
+pub use self::data::DataError;
+
 mod animation;
 pub use self::animation::{Animator, AnimatorBuilder, AnimationData};
 
@@ -36,6 +43,9 @@ pub use self::particle::{
     Particle, ParticleSystem, ParticleTemplate
 };
 
+mod renderer;
+pub use self::renderer::Renderer;
+
 pub mod library;
 
 mod ragdoll;
@@ -44,6 +54,12 @@ pub use self::ragdoll::Ragdoll;
 mod rigid_body;
 pub use self::rigid_body::{RigidBodyData, RigidBody};
 
+mod pose;
+pub use self::pose::Pose;
+
 mod skeleton;
-pub use self::skeleton::{SkeletalData, SkeletalConstraint, Skeleton};
+pub use self::skeleton::{
+    SkeletalData, SkeletalConstraint, Skeleton, SkeletonError, BoneId, BoneHit, LookAtId,
+    IKGoalId, IKTarget, IKReach, IKOptions, IKSolver, RagdollFacing, DebugDrawOptions
+};
 
@@ -11,7 +11,7 @@ use downcast_rs::Downcast;
 
 
 // Internal Dependencies ------------------------------------------------------
-use ::{Skeleton, Vec2};
+use ::{IKTarget, Skeleton, Vec2};
 
 
 // Modules --------------------------------------------------------------------
@@ -23,18 +23,12 @@ pub use self::weapon::Weapon;
 
 mod stick_figure;
 pub use self::stick_figure::{
-    StickFigureConfig, StickFigureState, StickFigure
+    StickFigureConfig, StickFigureState, StickFigure, StickFigureLimb
 };
 
 
 // Traits ---------------------------------------------------------------------
-pub trait Renderer {
-    fn dt(&self) -> f32;
-    fn time(&self) -> f32;
-    fn draw_line(&mut self, start: Vec2, end: Vec2, color: u32);
-    fn draw_circle(&mut self, c: Vec2, r: f32, color: u32);
-    fn draw_rect(&mut self, tr: Vec2, bl: Vec2, color: u32);
-}
+pub use ::Renderer;
 
 pub trait Collider {
     fn world(&self, Vec2) -> Option<(Vec2, i32, i32)>;
@@ -42,11 +36,12 @@ pub trait Collider {
 
 pub trait Accessory<R: Renderer, C: Collider>: Downcast {
     fn set_bone(&mut self, &'static str);
+    fn bone(&self) -> &'static str;
     fn attach(&mut self, skeleton: &Skeleton);
     fn attached(&self) -> bool;
     fn detach(&mut self, skeleton: &Skeleton);
     fn apply_force(&mut self, force: Vec2);
-    fn get_iks(&self, skeleton: &Skeleton) -> Option<Vec<(&'static str, Vec2, bool)>>;
+    fn get_iks(&self, skeleton: &Skeleton) -> Option<Vec<(&'static str, IKTarget, bool)>>;
     fn fixate(&mut self, skeleton: &Skeleton);
     fn set_gravity(&mut self, gravity: Vec2);
     fn step(&mut self, renderer: &R, collider: &C);
@@ -8,7 +8,7 @@
 
 
 // Internal Dependencies ------------------------------------------------------
-use ::{Vec2, Space, ParticleSystem, ParticleTemplate, Skeleton};
+use ::{Vec2, Space, ParticleSystem, ParticleTemplate, Skeleton, IKTarget};
 use ::library::{Accessory, Renderer, Collider};
 
 
@@ -19,7 +19,8 @@ pub struct Scarf {
     color: u32,
     offset: Vec2,
     facing: Vec2,
-    gravity: Vec2
+    gravity: Vec2,
+    attached: bool
 }
 
 impl Scarf {
@@ -37,6 +38,7 @@ impl Scarf {
             offset: Vec2::zero(),
             gravity: Vec2::zero(),
             facing: Vec2::new(1.0, 1.0),
+            attached: true
         }
     }
 
@@ -48,31 +50,60 @@ impl<R: Renderer, C: Collider> Accessory<R, C> for Scarf {
         self.bone = bone;
     }
 
-    fn attach(&mut self, _: &Skeleton) {
-        self.particles.visit_particles_mut(|_, p| {
+    fn bone(&self) -> &'static str {
+        self.bone
+    }
+
+    fn attach(&mut self, skeleton: &Skeleton) {
+        if skeleton.is_attachment_severed(self.bone) {
+            return;
+        }
+        self.attached = true;
+        self.particles.visit_particles_mut(|i, p| {
             p.set_position(Vec2::zero());
+            if i == 0 {
+                p.set_invmass(0.0);
+            }
         });
     }
 
     fn attached(&self) -> bool {
-        true
+        self.attached
     }
 
-    fn detach(&mut self, _: &Skeleton) {}
+    fn detach(&mut self, skeleton: &Skeleton) {
+        // Scarfs are worn and only come off along with their bone
+        if self.attached && skeleton.is_attachment_severed(self.bone) {
+            self.attached = false;
+            self.particles.get_mut(0).set_invmass(0.90);
+        }
+    }
 
-    fn apply_force(&mut self, _: Vec2) {}
+    fn apply_force(&mut self, force: Vec2) {
+        if !self.attached {
+            self.particles.activate();
+            self.particles.get_mut(0).apply_force(force);
+        }
+    }
 
-    fn get_iks(&self, _: &Skeleton) -> Option<Vec<(&'static str, Vec2, bool)>> {
+    fn get_iks(&self, _: &Skeleton) -> Option<Vec<(&'static str, IKTarget, bool)>> {
         None
     }
 
     fn fixate(&mut self, skeleton: &Skeleton) {
+        if !self.attached {
+            return;
+        }
+
         // TODO simpler way to get the offset?
-        let origin = skeleton.bone_end(Space::Local, self.bone);
-        let offset = skeleton.bone_end(Space::World, self.bone) - origin;
-        self.facing = skeleton.local_transform();
-        self.particles.get_mut(0).set_position(origin);
-        self.offset = offset;
+        if let (Ok(origin), Ok(world)) = (
+            skeleton.attachment_point(Space::Local, self.bone),
+            skeleton.attachment_point(Space::World, self.bone)
+        ) {
+            self.facing = skeleton.local_transform();
+            self.particles.get_mut(0).set_position(origin);
+            self.offset = world - origin;
+        }
     }
 
     fn set_gravity(&mut self, gravity: Vec2) {
@@ -84,10 +115,18 @@ impl<R: Renderer, C: Collider> Accessory<R, C> for Scarf {
         // Don't let the scarf fall into rest
         self.particles.activate();
 
+        // Worn scarfs flutter in the wind, dropped ones simply fall
+        let gravity = if self.attached {
+            Vec2::new(-200.0 * self.facing.x, (renderer.time() * 4.0).sin() * self.gravity.y * 0.5)
+
+        } else {
+            self.gravity
+        };
+
         let world_offset = self.offset;
         self.particles.step(
             renderer.dt(),
-            Vec2::new(-200.0 * self.facing.x, (renderer.time() * 4.0).sin() * self.gravity.y * 0.5),
+            gravity,
             |p| {
                 if let Some((pos, _, _)) = collider.world(p.position + world_offset) {
                     p.position = pos - world_offset;
@@ -104,4 +143,3 @@ impl<R: Renderer, C: Collider> Accessory<R, C> for Scarf {
     }
 
 }
-
@@ -9,12 +9,14 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::f32::consts::PI;
+use std::sync::Arc;
 use std::collections::HashMap;
 
 
 // Internal Dependencies ------------------------------------------------------
 use ::{
-    Skeleton, SkeletalData, SkeletalConstraint,
+    Ragdoll, Skeleton, SkeletalData, SkeletalConstraint, BoneId, LookAtId, IKGoalId, IKTarget, IKReach,
+    RagdollFacing,
     AnimatorBuilder, AnimationData,
     Angle, Vec2, Space,
     f32_equals
@@ -28,229 +30,288 @@ const D90: f32 = PI * 0.5;
 const D45: f32 = D90 * 0.5;
 const D22: f32 = D45 * 0.5;
 const D12: f32 = D22 * 0.5;
+const KNEE_POLE_DISTANCE: f32 = 40.0;
 
 lazy_static! {
 
-    static ref DEFAULT_FIGURE_SKELETON: SkeletalData = SkeletalData {
+    // Bone limits are relative to the parent and positive angles rotate
+    // clockwise with the figure facing right. So elbows bend counter
+    // clockwise (negative) and knees clockwise (positive), matching the
+    // angles used by the animations below. Hands may overextend slightly
+    // for the jump animation.
+    static ref DEFAULT_FIGURE_SKELETON: Arc<SkeletalData> = Arc::new(SkeletalData {
         bones: vec![
-            (  "Root", ( "Root",  0.0, -D90, 0.98, None, None)), // 0
+            (  "Root".into(), ( "Root".into(),  0.0, -D90, 0.98, None, None)), // 0
 
-            (  "Back", ( "Root", 18.0,  0.0, 0.99, None, None)), // 1
-            (  "Head", ( "Back", 10.0,  0.0, 0.99, None, None)), // 2
+            (  "Back".into(), ( "Root".into(), 18.0,  0.0, 0.99, None, None)), // 1
+            (  "Head".into(), ( "Back".into(), 10.0,  0.0, 0.99, None, None)), // 2
 
-            ( "R.Arm", ( "Back",  9.0,  D90, 1.00, None, None)), // 3
-            ("R.Hand", ("R.Arm", 13.0,  0.0, 1.00, Some(0.0), Some(D90 * 1.9))), // 4
-            ( "L.Arm", ( "Back",  9.0, -D90, 1.00, None, None)),  // 5
-            ("L.Hand", ("L.Arm", 13.0,  0.0, 1.00, Some(0.0), Some(D90 * 1.9))), // 6
+            ( "R.Arm".into(), ( "Back".into(),  9.0,  D90, 1.00, None, None)), // 3
+            ("R.Hand".into(), ("R.Arm".into(), 13.0,  0.0, 1.00, Some(-D90 * 1.9), Some(D45 * 1.1))), // 4
+            ( "L.Arm".into(), ( "Back".into(),  9.0, -D90, 1.00, None, None)),  // 5
+            ("L.Hand".into(), ("L.Arm".into(), 13.0,  0.0, 1.00, Some(-D90 * 1.9), Some(D45 * 1.1))), // 6
 
-            (   "Hip", ( "Root",   0.0,  PI, 1.00, None, None)), // 7
+            (   "Hip".into(), ( "Root".into(),   0.0,  PI, 1.00, None, None)), // 7
 
-            ( "R.Leg", (  "Hip", 13.0,  0.0, 0.99, None, None)), // 8
-            ("R.Foot", ("R.Leg", 14.0,  0.0, 1.00, Some(-D90 * 1.9), Some(0.0))), // 9
-            ( "L.Leg", (  "Hip", 13.0,  0.0, 0.99, None, None)), // 10
-            ("L.Foot", ("L.Leg", 14.0,  0.0, 1.00, Some(-D90 * 1.9), Some(0.0))), // 11
+            ( "R.Leg".into(), (  "Hip".into(), 13.0,  0.0, 0.99, None, None)), // 8
+            ("R.Foot".into(), ("R.Leg".into(), 14.0,  0.0, 1.00, Some(0.0), Some(D90 * 1.9))), // 9
+            ( "L.Leg".into(), (  "Hip".into(), 13.0,  0.0, 0.99, None, None)), // 10
+            ("L.Foot".into(), ("L.Leg".into(), 14.0,  0.0, 1.00, Some(0.0), Some(D90 * 1.9))), // 11
+
+            ( "R.Toe".into(), ("R.Foot".into(), 4.0, -D90, 1.00, Some(-D90 - D45), Some(-D45))), // 12
+            ( "L.Toe".into(), ("L.Foot".into(), 4.0, -D90, 1.00, Some(-D90 - D45), Some(-D45))), // 13
         ],
-        ragdoll_parents: vec![
-            // Skip hip during ragdolls
-            ("L.Leg", "Root"),
-            ("R.Leg", "Root")
+        ragdoll_parents: vec![],
+        // Unlimited, examples/stick_example/figure.ron limits knees and elbows
+        ragdoll_splits: vec![
+            ("L.Leg".into(), None),
+            ("R.Leg".into(), None),
+            ("L.Arm".into(), None),
+            ("R.Arm".into(), None)
         ],
         constraints: vec![
-            SkeletalConstraint::Stick("Back", "L.Leg"),
-            SkeletalConstraint::Stick("Back", "R.Leg"),
+            SkeletalConstraint::Stick("Back".into(), "L.Leg".into()),
+            SkeletalConstraint::Stick("Back".into(), "R.Leg".into()),
             // SkeletalConstraint::Stick("Head", "Root"),
 
-            SkeletalConstraint::Angular("Root", "Back", "Head", PI - D45, PI - D45),
-            // SkeletalConstraint::Angular("Root", "L.Leg", "L.Foot", PI, PI - D90 * 1.90),
-            // SkeletalConstraint::Angular("Root", "R.Leg", "R.Foot", PI, PI - D90 * 1.90),
-            // SkeletalConstraint::Angular("Root", "L.Arm", "L.Hand", PI, PI - D90 * 1.5),
-            // SkeletalConstraint::Angular("Root", "R.Arm", "R.Hand", PI, PI - D90 * 1.5)
+            SkeletalConstraint::Angular("Root".into(), "Back".into(), "Head".into(), PI - D45, PI - D45)
 
+        ],
+        sockets: vec![],
+        hitboxes: vec![
+            (  "Back".into(), 2.5),
+            (  "Head".into(), 4.0),
+            ( "R.Arm".into(), 1.5),
+            ("R.Hand".into(), 1.5),
+            ( "L.Arm".into(), 1.5),
+            ("L.Hand".into(), 1.5),
+            ( "R.Leg".into(), 2.0),
+            ("R.Foot".into(), 1.5),
+            ( "L.Leg".into(), 2.0),
+            ("L.Foot".into(), 1.5)
         ]
 
 
-    };
+    });
 
-    static ref IDLE_ANIMATION: AnimationData = AnimationData {
+    static ref IDLE_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
         duration: 1.25,
         key_frames: vec![
             // Pose
             (0.0, vec![
-                ( "L.Leg", -D45),
-                ("L.Foot",  D45),
-                ( "R.Leg",  0.0),
-                ("R.Foot",  D45),
-
-                ( "R.Arm", D90 * 1.25),
-                ("R.Hand",  -D90),
-                ( "L.Arm",  -D90 * 0.5),
-                ("L.Hand",  -D45 * 1.65)
+                ( "L.Leg".into(), -D45),
+                ("L.Foot".into(),  D45),
+                ( "R.Leg".into(),  0.0),
+                ("R.Foot".into(),  D45),
+
+                ( "R.Arm".into(), D90 * 1.25),
+                ("R.Hand".into(),  -D90),
+                ( "L.Arm".into(),  -D90 * 0.5),
+                ("L.Hand".into(),  -D45 * 1.65)
             ]),
             // Matches the idle compression
             (1.25 * 0.5, vec![
-                ( "L.Leg", -D45),
-                ("L.Foot",  D45),
-                ( "R.Leg",  0.0),
-                ("R.Foot",  D45),
-
-                ( "R.Arm", D90 * 1.0),
-                ("R.Hand",  -D90),
-                ( "L.Arm",  -D90 * 0.75),
-                ("L.Hand",  -D45 * 1.65)
+                ( "L.Leg".into(), -D45),
+                ("L.Foot".into(),  D45),
+                ( "R.Leg".into(),  0.0),
+                ("R.Foot".into(),  D45),
+
+                ( "R.Arm".into(), D90 * 1.0),
+                ("R.Hand".into(),  -D90),
+                ( "L.Arm".into(),  -D90 * 0.75),
+                ("L.Hand".into(),  -D45 * 1.65)
+            ])
+        ]
+    });
+
+    static ref GET_UP_FACE_UP_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
+        duration: 1.0,
+        key_frames: vec![
+            // Sitting up with both arms pushing off behind the back
+            (0.0, vec![
+                (  "Back".into(), -D22),
+                ( "L.Leg".into(), -D90 * 1.1),
+                ("L.Foot".into(),  D90 * 1.8),
+                ( "R.Leg".into(), -D90 * 0.9),
+                ("R.Foot".into(),  D90 * 1.8),
+
+                ( "R.Arm".into(), D90 * 1.5),
+                ("R.Hand".into(),  0.0),
+                ( "L.Arm".into(), -D45),
+                ("L.Hand".into(),  0.0)
+            ])
+        ]
+    });
+
+    static ref GET_UP_FACE_DOWN_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
+        duration: 1.0,
+        key_frames: vec![
+            // Kneeling with both arms pushing off in front
+            (0.0, vec![
+                (  "Back".into(), D45),
+                ( "L.Leg".into(), -D45),
+                ("L.Foot".into(),  D90 * 1.8),
+                ( "R.Leg".into(),  D12),
+                ("R.Foot".into(),  D90 * 1.5),
+
+                ( "R.Arm".into(), D45),
+                ("R.Hand".into(),  0.0),
+                ( "L.Arm".into(), -D90 * 1.5),
+                ("L.Hand".into(),  0.0)
             ])
         ]
-    };
+    });
 
-    static ref JUMP_ANIMATION: AnimationData = AnimationData {
+    static ref JUMP_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
         duration: 0.6,
         key_frames: vec![
             // 1
             (0.0, vec![
-                ( "L.Leg", -D12 * 3.5 + -D22),
-                ("L.Foot",  D22 * 5.0),
-                ( "R.Leg", -D12 * 5.0 +  D22),
-                ("R.Foot",  D22 * 5.0),
+                ( "L.Leg".into(), -D12 * 3.5 + -D22),
+                ("L.Foot".into(),  D22 * 5.0),
+                ( "R.Leg".into(), -D12 * 5.0 +  D22),
+                ("R.Foot".into(),  D22 * 5.0),
 
-                ( "R.Arm",  -D45 * 1.25),
-                ("R.Hand",  -D45 * 1.25),
+                ( "R.Arm".into(),  -D45 * 1.25),
+                ("R.Hand".into(),  -D45 * 1.25),
 
-                ( "L.Arm",  D45 * 1.05),
-                ("L.Hand",  D45 * 1.05)
+                ( "L.Arm".into(),  D45 * 1.05),
+                ("L.Hand".into(),  D45 * 1.05)
             ]),
             // 2
             (0.3, vec![
-                ( "R.Leg", -D12 * 5.0 + -D22),
-                ("R.Foot",  D22 * 4.0),
-                ( "L.Leg", -D12 * 3.5 +  D22),
-                ("L.Foot",  D22 * 4.0),
+                ( "R.Leg".into(), -D12 * 5.0 + -D22),
+                ("R.Foot".into(),  D22 * 4.0),
+                ( "L.Leg".into(), -D12 * 3.5 +  D22),
+                ("L.Foot".into(),  D22 * 4.0),
 
-                ( "R.Arm",  -D45 * 0.5),
-                ("R.Hand",  -D45 * 0.5),
+                ( "R.Arm".into(),  -D45 * 0.5),
+                ("R.Hand".into(),  -D45 * 0.5),
 
-                ( "L.Arm",  D45 * 0.4),
-                ("L.Hand",  D45 * 0.4)
+                ( "L.Arm".into(),  D45 * 0.4),
+                ("L.Hand".into(),  D45 * 0.4)
             ]),
         ]
-    };
+    });
 
-    static ref RUN_ANIMATION: AnimationData = AnimationData {
+    static ref RUN_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
         duration: 1.0,
         key_frames: vec![
             // Pass
             (0.0, vec![
-                ( "L.Leg", -D45 * 1.15),
-                ("L.Foot",  D45 * 1.95),
-                ( "R.Leg", -D12),
-                ("R.Foot",  D45),
+                ( "L.Leg".into(), -D45 * 1.15),
+                ("L.Foot".into(),  D45 * 1.95),
+                ( "R.Leg".into(), -D12),
+                ("R.Foot".into(),  D45),
 
-                ( "R.Arm", D90 * 1.25),
-                ("R.Hand",  -D90 * 0.90),
-                ( "L.Arm", -D90 * 0.75),
-                ("L.Hand",  -D90 * 0.90),
+                ( "R.Arm".into(), D90 * 1.25),
+                ("R.Hand".into(),  -D90 * 0.90),
+                ( "L.Arm".into(), -D90 * 0.75),
+                ("L.Hand".into(),  -D90 * 0.90),
 
             ]),
             // Reach
             (0.3, vec![
-                ( "L.Leg",  -D45 * 0.95),
-                ("L.Foot",  D12 * 0.5),
-                ( "R.Leg",  D45),
-                ("R.Foot",  D45 * 1.35),
+                ( "L.Leg".into(),  -D45 * 0.95),
+                ("L.Foot".into(),  D12 * 0.5),
+                ( "R.Leg".into(),  D45),
+                ("R.Foot".into(),  D45 * 1.35),
 
-                ( "R.Arm", D90 * 1.95),
-                ("R.Hand",  -D90 * 0.90),
+                ( "R.Arm".into(), D90 * 1.95),
+                ("R.Hand".into(),  -D90 * 0.90),
 
-                ( "L.Arm", -D90 * 1.45),
-                ("L.Hand",  -D90 * 0.90),
+                ( "L.Arm".into(), -D90 * 1.45),
+                ("L.Hand".into(),  -D90 * 0.90),
 
             ]),
             // Pass with Legs Swapped
             (0.5, vec![
-                ( "R.Leg", -D45 * 1.15),
-                ("R.Foot",  D45 * 1.95),
-                ( "L.Leg", -D12),
-                ("L.Foot",  D45),
+                ( "R.Leg".into(), -D45 * 1.15),
+                ("R.Foot".into(),  D45 * 1.95),
+                ( "L.Leg".into(), -D12),
+                ("L.Foot".into(),  D45),
 
-                ( "R.Arm", D90 * 1.25), // TODO slightly adjust
-                ("R.Hand",  -D90 * 0.90),
-                ( "L.Arm", -D90 * 0.75), // TODO slightly adjust
-                ("L.Hand",  -D90 * 0.90),
+                ( "R.Arm".into(), D90 * 1.25), // TODO slightly adjust
+                ("R.Hand".into(),  -D90 * 0.90),
+                ( "L.Arm".into(), -D90 * 0.75), // TODO slightly adjust
+                ("L.Hand".into(),  -D90 * 0.90),
 
             ]),
             // Reach Mirrored
             (0.8, vec![
-                ( "R.Leg",  -D45 * 0.95),
-                ("R.Foot",  D12 * 0.5),
-                ( "L.Leg",  D45),
-                ("L.Foot",  D45 * 1.35),
-
-                ( "R.Arm", D90 * 0.5),
-                ("R.Hand",  -D90 * 0.90),
-                ( "L.Arm", -D90 * 0.05),
-                ("L.Hand",  -D90 * 0.90),
+                ( "R.Leg".into(),  -D45 * 0.95),
+                ("R.Foot".into(),  D12 * 0.5),
+                ( "L.Leg".into(),  D45),
+                ("L.Foot".into(),  D45 * 1.35),
+
+                ( "R.Arm".into(), D90 * 0.5),
+                ("R.Hand".into(),  -D90 * 0.90),
+                ( "L.Arm".into(), -D90 * 0.05),
+                ("L.Hand".into(),  -D90 * 0.90),
             ])
         ]
-    };
+    });
 
-    static ref WALK_BACKWARDS_ANIMATION: AnimationData = AnimationData {
+    static ref WALK_BACKWARDS_ANIMATION: Arc<AnimationData> = Arc::new(AnimationData {
         duration: 0.8,
         key_frames: vec![
 
             // Pass
             (0.0, vec![
-                ( "L.Leg", -D12 * 1.25),
-                ("L.Foot", D45 * 1.5),
-                ( "R.Leg", D12 * 0.25),
-                ("R.Foot", D22 * 0.25),
-
-                ( "R.Arm", D90 * 1.25),
-                ("R.Hand",  -D90 * 0.40),
-                ( "L.Arm", -D90 * 0.85),
-                ("L.Hand",  -D90 * 0.50)
+                ( "L.Leg".into(), -D12 * 1.25),
+                ("L.Foot".into(), D45 * 1.5),
+                ( "R.Leg".into(), D12 * 0.25),
+                ("R.Foot".into(), D22 * 0.25),
+
+                ( "R.Arm".into(), D90 * 1.25),
+                ("R.Hand".into(),  -D90 * 0.40),
+                ( "L.Arm".into(), -D90 * 0.85),
+                ("L.Hand".into(),  -D90 * 0.50)
             ]),
 
             // Reach
             (0.24, vec![
-                ( "L.Leg", -D22 * 1.25),
-                ("L.Foot", D12),
-                ( "R.Leg", D12),
-                ("R.Foot", D22),
-
-                ( "R.Arm", D90 * 1.50),
-                ("R.Hand",  -D90 * 0.50),
-                ( "L.Arm", -D90 * 1.15),
-                ("L.Hand",  -D90 * 0.50)
+                ( "L.Leg".into(), -D22 * 1.25),
+                ("L.Foot".into(), D12),
+                ( "R.Leg".into(), D12),
+                ("R.Foot".into(), D22),
+
+                ( "R.Arm".into(), D90 * 1.50),
+                ("R.Hand".into(),  -D90 * 0.50),
+                ( "L.Arm".into(), -D90 * 1.15),
+                ("L.Hand".into(),  -D90 * 0.50)
             ]),
 
             // // Pass
             (0.4, vec![
-                ( "R.Leg", -D12 * 1.25),
-                ("R.Foot", D45 * 1.5),
-                ( "L.Leg", D12 * 0.25),
-                ("L.Foot", D22 * 0.25),
-
-                ( "R.Arm", D90 * 1.25),
-                ("R.Hand",  -D90 * 0.40),
-                ( "L.Arm", -D90 * 0.85),
-                ("L.Hand",  -D90 * 0.50)
+                ( "R.Leg".into(), -D12 * 1.25),
+                ("R.Foot".into(), D45 * 1.5),
+                ( "L.Leg".into(), D12 * 0.25),
+                ("L.Foot".into(), D22 * 0.25),
+
+                ( "R.Arm".into(), D90 * 1.25),
+                ("R.Hand".into(),  -D90 * 0.40),
+                ( "L.Arm".into(), -D90 * 0.85),
+                ("L.Hand".into(),  -D90 * 0.50)
             ]),
 
             // Reach
             (0.64, vec![
-                ( "R.Leg", -D22 * 1.25),
-                ("R.Foot", D12),
-                ( "L.Leg", D12),
-                ("L.Foot", D22),
+                ( "R.Leg".into(), -D22 * 1.25),
+                ("R.Foot".into(), D12),
+                ( "L.Leg".into(), D12),
+                ("L.Foot".into(), D22),
 
-                ( "R.Arm", D90 * 1.00),
-                ("R.Hand",  -D90 * 0.60),
+                ( "R.Arm".into(), D90 * 1.00),
+                ("R.Hand".into(),  -D90 * 0.60),
 
-                ( "L.Arm", -D90 * 0.60),
-                ("L.Hand",  -D90 * 0.60)
+                ( "L.Arm".into(), -D90 * 0.60),
+                ("L.Hand".into(),  -D90 * 0.60)
 
             ])
 
         ]
-    };
+    });
 
 }
 
@@ -286,9 +347,19 @@ pub struct StickFigureConfig {
     pub fall_speed: f32,
     pub fall_limit: f32,
 
-    pub leanback_min: f32,
-    pub leanback_max: f32,
+    // (min, max) angles by which the back and head turn towards the aim target
+    pub look_at_back_limits: (f32, f32),
+    pub look_at_head_limits: (f32, f32),
     pub leanback_head_factor: f32,
+    pub aim_turn_speed: f32,
+    pub ik_blend_duration: f32,
+    pub ik_swing_duration: f32,
+    pub ik_max_stretch: f32,
+    pub foot_probe_height: f32,
+    pub hip_drop_max: f32,
+    pub foot_lock_speed: f32,
+    pub foot_lock_distance: f32,
+    pub foot_lock_release: f32,
 
     pub recoil_leanback_factor: f32,
     pub recoil_force: f32,
@@ -306,7 +377,28 @@ pub struct StickFigureConfig {
 
     pub crouching_factor: f32,
     pub crouch_compression: f32,
-    pub crouch_speed: f32
+    pub crouch_speed: f32,
+
+    pub get_up_duration: f32
+}
+
+
+// Bone Handles ---------------------------------------------------------------
+#[derive(Copy, Clone)]
+struct StickFigureBones {
+    back: Option<BoneId>,
+    head: Option<BoneId>
+}
+
+impl StickFigureBones {
+
+    fn new(skeleton: &Skeleton) -> Self {
+        Self {
+            back: skeleton.bone_id("Back"),
+            head: skeleton.bone_id("Head")
+        }
+    }
+
 }
 
 
@@ -319,6 +411,11 @@ pub struct StickFigure<T: StickFigureState, R: Renderer, C: Collider> {
 
     // Rendering Only
     skeleton: Skeleton,
+    bones: StickFigureBones,
+    look_at: Option<LookAtId>,
+    aim_target: Option<Vec2>,
+    foot_goals: Vec<IKGoalId>,
+    accessory_goals: HashMap<&'static str, IKGoalId>,
     idle_timer: f32,
     run_timer: f32,
     crouch_timer: f32,
@@ -332,34 +429,42 @@ pub struct StickFigure<T: StickFigureState, R: Renderer, C: Collider> {
     accessories: HashMap<&'static str, Box<Accessory<R, C>>>,
 
     // Visual feedback
-    ragdoll_timer: f32
+    ragdoll_timer: f32,
+    get_up_timer: f32,
+    get_up_state: &'static str
 
 }
 
 impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFigure<T, R, C> {
 
     pub fn default(state: T, config: StickFigureConfig) -> Self {
-        StickFigure::from_skeleton(&DEFAULT_FIGURE_SKELETON, state, config)
+        StickFigure::from_skeleton(DEFAULT_FIGURE_SKELETON.clone(), state, config)
     }
 
     pub fn from_skeleton(
-        data: &'static SkeletalData,
+        data: Arc<SkeletalData>,
         state: T,
         config: StickFigureConfig
 
     ) -> Self {
 
         let animator = AnimatorBuilder::new().with_state("Idle", |s| {
-            s.add_animation(&IDLE_ANIMATION);
+            s.add_animation(IDLE_ANIMATION.clone());
 
         }).with_state("Jump", |s| {
-            s.add_animation(&JUMP_ANIMATION);
+            s.add_animation(JUMP_ANIMATION.clone());
 
         }).with_state("Run", |s| {
-            s.add_animation(&RUN_ANIMATION);
+            s.add_animation(RUN_ANIMATION.clone());
 
         }).with_state("Back", |s| {
-            s.add_animation(&WALK_BACKWARDS_ANIMATION);
+            s.add_animation(WALK_BACKWARDS_ANIMATION.clone());
+
+        }).with_state("GetUpFaceUp", |s| {
+            s.add_animation(GET_UP_FACE_UP_ANIMATION.clone());
+
+        }).with_state("GetUpFaceDown", |s| {
+            s.add_animation(GET_UP_FACE_DOWN_ANIMATION.clone());
 
         }).with_blend("*", "Back", 0.05)
           .with_blend("*", "Idle", 0.2)
@@ -370,11 +475,52 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
         let mut skeleton = Skeleton::new(data);
         skeleton.set_animator(animator);
 
+        let bones = StickFigureBones::new(&skeleton);
+
+        // Aim by turning the back and head towards the line of sight target
+        let look_at = skeleton.add_look_at("Head", "Back", D90, config.aim_turn_speed).ok();
+        if let Some(look_at) = look_at {
+            let (back, head) = (config.look_at_back_limits, config.look_at_head_limits);
+            skeleton.set_look_at_bone(look_at, "Back", 0.5, Some(back.0), Some(back.1)).ok();
+            skeleton.set_look_at_bone(look_at, "Head", 1.0, Some(head.0), Some(head.1)).ok();
+        }
+
+        // Feet reach for the ground below them while grounded, with the
+        // knees pointing forward and the toes following the slope
+        skeleton.set_ground_probe(config.foot_probe_height, config.hip_drop_max);
+
+        let foot_goals = [("L.Foot", "L.Toe"), ("R.Foot", "R.Toe")].iter().filter_map(|&(foot, toe)| {
+            let goal = skeleton.add_ik_goal(foot, false, 0).ok()?;
+            skeleton.set_ik_goal_align(goal, Some(toe)).ok();
+            Some(goal)
+
+        }).collect::<Vec<IKGoalId>>();
+
+        for goal in &foot_goals {
+            let knee_pole = (Space::Animation, Vec2::new(KNEE_POLE_DISTANCE, 0.0));
+            skeleton.set_ik_goal_blend(*goal, config.ik_blend_duration, config.ik_blend_duration);
+            skeleton.set_ik_goal_pole(*goal, Some(knee_pole), config.ik_swing_duration);
+            skeleton.set_ik_goal_reach(*goal, IKReach::Extend);
+
+            // Keep planted feet from sliding along the ground
+            skeleton.set_ik_goal_lock(
+                *goal,
+                config.foot_lock_speed,
+                config.foot_lock_distance,
+                config.foot_lock_release
+            );
+        }
+
         Self {
             config: config,
             state: state,
 
             skeleton: skeleton,
+            bones,
+            look_at,
+            aim_target: None,
+            foot_goals,
+            accessory_goals: HashMap::new(),
             crouch_timer: 0.0,
             idle_timer: 0.0,
             run_timer: 0.0,
@@ -385,6 +531,8 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
             was_grounded: false,
 
             ragdoll_timer: 0.0,
+            get_up_timer: 0.0,
+            get_up_state: "GetUpFaceUp",
 
             accessories: HashMap::new()
         }
@@ -395,11 +543,12 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
     pub fn add_accessory<A: Accessory<R, C> + 'static>(
         &mut self,
         name: &'static str,
-        bone: &'static str,
+        attachment: &'static str,
         accessory: A
     ) {
+        // Accessories attach to either a named socket or a bone's end point
         let mut a = Box::new(accessory) as Box<Accessory<R, C>>;
-        a.set_bone(bone);
+        a.set_bone(attachment);
         self.accessories.insert(name, a);
     }
 
@@ -409,7 +558,9 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
 
     pub fn attach(&mut self, name: &'static str) {
         if let Some(accessory) = self.accessories.get_mut(name) {
-            accessory.attach(&self.skeleton);
+            if !self.skeleton.is_attachment_severed(accessory.bone()) {
+                accessory.attach(&self.skeleton);
+            }
         }
     }
 
@@ -419,6 +570,27 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
         }
     }
 
+    pub fn sever(&mut self, bone: &str, at_length: Option<f32>) -> Option<StickFigureLimb> {
+        let scale = self.skeleton.transform().scale;
+        let ragdoll = self.skeleton.sever(bone, at_length).ok()??;
+
+        // Drop accessories which were attached to any of the severed bones
+        let force = self.accessory_force();
+        for accessory in self.accessories.values_mut() {
+            if accessory.attached() && self.skeleton.is_attachment_severed(accessory.bone()) {
+                accessory.detach(&self.skeleton);
+                accessory.apply_force(force);
+            }
+        }
+
+        Some(StickFigureLimb {
+            bone: bone.to_string(),
+            ragdoll,
+            scale,
+            ragdoll_timer: 0.0
+        })
+    }
+
     pub fn get_accessory_mut<A: Accessory<R, C>>(&mut self, name: &'static str) -> Option<&mut A> {
         if let Some(a) = self.accessories.get_mut(name) {
             a.downcast_mut::<A>()
@@ -448,34 +620,54 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
 
 
     // Setters ----------------------------------------------------------------
+    // World space point the head and weapon aim at, without one the figure
+    // looks straight ahead
+    pub fn set_aim_target(&mut self, target: Option<Vec2>) {
+        self.aim_target = target;
+    }
+
     pub fn set_state(&mut self, state: T) {
 
         self.state = state;
 
         if !self.state.is_alive() && !self.skeleton.has_ragdoll() {
 
-            let facing = Angle::facing(self.state.direction() + D90).to_vec();
-            let force = self.state.force().scale(facing) + self.state.velocity();
-
             // Update weapon model to support ragdoll
+            let force = self.accessory_force();
             for accessory in self.accessories.values_mut() {
                 let was_attached = accessory.attached();
                 accessory.detach(&self.skeleton);
                 if was_attached {
-                    accessory.apply_force(force * 0.5);
+                    accessory.apply_force(force);
                 }
             }
 
-            // Setup skeleton ragdoll
-            self.skeleton.start_ragdoll();
-            self.skeleton.apply_local_force(Vec2::new(0.0, -10.0), force, 2.0);
+            // Setup skeleton ragdoll, which keeps the momentum of the animation
+            // and only receives the impact on top of it
+            if self.skeleton.start_ragdoll().is_ok() {
+                let facing = Angle::facing(self.state.direction() + D90).to_vec();
+                let force = self.state.force().scale(facing);
+                self.skeleton.apply_local_force(Vec2::new(0.0, -10.0), force, 2.0);
+            }
             self.ragdoll_timer = 0.0;
 
         } else if self.state.is_alive() && self.skeleton.has_ragdoll() {
             for accessory in self.accessories.values_mut() {
-                accessory.attach(&self.skeleton);
+                if !self.skeleton.is_attachment_severed(accessory.bone()) {
+                    accessory.attach(&self.skeleton);
+                }
+            }
+
+            // Blend from the ragdoll into the matching get up animation
+            let duration = self.config.get_up_duration;
+            if let Some(facing) = self.skeleton.recover_from_ragdoll(duration) {
+                self.get_up_state = match facing {
+                    RagdollFacing::Up => "GetUpFaceUp",
+                    RagdollFacing::Down => "GetUpFaceDown"
+                };
+                self.get_up_timer = duration;
             }
-            self.skeleton.stop_ragdoll();
+
         }
 
     }
@@ -496,24 +688,29 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
         let velocity = self.state.velocity();
         let position = self.state.position();
         let ragdoll_timer = self.ragdoll_timer;
+        let bones = self.bones;
 
         self.skeleton.set_local_transform(facing);
 
-        // Aim Leanback
-        let aim_horizon = self.compute_view_horizon_distance();
-        let leanback = (
-            aim_horizon * 0.5
-            - self.recoil * self.config.recoil_leanback_factor
+        // Recoil Leanback
+        let leanback = -self.recoil * self.config.recoil_leanback_factor * 0.009;
 
-        ).min(self.config.leanback_max).max(self.config.leanback_min) * 0.009;;
+        if let Some(back) = bones.back {
+            self.skeleton.apply_bone_angle_by_id(back, leanback + velocity.x * 0.05 * facing.x);
+        }
 
-        self.skeleton.apply_bone_angle("Back", leanback + velocity.x * 0.05 * facing.x);
-        self.skeleton.apply_bone_angle("Head", leanback * self.config.leanback_head_factor);
+        if let Some(head) = bones.head {
+            self.skeleton.apply_bone_angle_by_id(head, leanback * self.config.leanback_head_factor);
+        }
 
         // Update Animations
         let run_factor = (1.0 / 3.5 * velocity.x).abs();
         let walk_backwards_factor = (self.config.velocity_backwards_factor / (3.5 * 0.5) * velocity.x).abs();
-        if !self.state.is_grounded() {
+        if self.get_up_timer > 0.0 {
+            self.skeleton.animator().set_speed(self.get_up_state, 1.0);
+            self.skeleton.animator().transition_to(self.get_up_state);
+
+        } else if !self.state.is_grounded() {
             self.skeleton.animator().set_speed("Jump", velocity.x.abs().max(1.0).min(1.5));
             self.skeleton.animator().transition_to("Jump");
 
@@ -551,6 +748,27 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
             position + self.config.offset + idle_offset + crouch_offset + run_offset + compression + recoil_compression
         );
 
+        // Aim Target
+        let aim_target = self.compute_aim_target();
+        if let Some(look_at) = self.look_at {
+            self.skeleton.set_look_at_target(look_at, Some(aim_target));
+        }
+
+        // Leg IKs
+        let foot_target = if self.state.is_grounded() {
+            Some(IKTarget::Ground)
+
+        } else {
+            None
+        };
+
+        for goal in &self.foot_goals {
+            self.skeleton.set_ik_goal_target(*goal, foot_target);
+        }
+
+        // Accessory IKs, these take priority over the legs
+        self.update_accessory_goals();
+
         // Animate and Arrange
         let world_offset = self.skeleton.world_offset();
         self.skeleton.step(dt, Vec2::new(0.0, self.config.fall_limit * 100.0), |p| {
@@ -562,57 +780,18 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
             }
         });
 
-        // Accessory IKs
-        for accessory in self.accessories.values() {
-            if let Some(iks) = accessory.get_iks(&self.skeleton) {
-                for (bone, p, positive) in iks {
-                    self.skeleton.apply_bone_ik(bone, p, positive, false);
-                }
-            }
-        }
-
-        // Leg IKs
-        if self.state.is_grounded() {
-            let foot_l = self.skeleton.bone_end(Space::Local, "L.Foot");
-            if let Some((p, _, _)) = collider.world(foot_l + world_offset) {
-                self.skeleton.apply_bone_ik("L.Foot", p - world_offset, false, true);
-                //self.skeleton.apply_bone_ik_new(p - world_offset - Vec2::new(0.0, 0.0), "L.Foot", "Hip", true);
-            }
-
-            let foot_r = self.skeleton.bone_end(Space::Local, "R.Foot");
-            if let Some((p, _, _)) = collider.world(foot_r + world_offset) {
-                self.skeleton.apply_bone_ik("R.Foot", p - world_offset, false, true);
-                //self.skeleton.apply_bone_ik_new(p - world_offset, "R.Foot", "Hip", true);
-            }
-        }
-
         // Draw bones
+        let scale = self.skeleton.transform().scale;
         self.skeleton.visit(|start, end, name| {
-
-            let line = (
-                start + world_offset,
-                end + world_offset
-            );
-
-            if name == "R.Arm" || name == "R.Hand" || name == "R.Leg" || name == "R.Foot" {
-                renderer.draw_line(line.0, line.1, 0x0080_8080);
-
-            } else if name != "Root" && name != "Head" {
-                renderer.draw_line(line.0, line.1, 0x00d0_d0d0);
-            }
+            draw_bone(renderer, name, start + world_offset, end + world_offset, scale);
 
         }, true);
 
-        // Draw Head
-        let head_end = self.skeleton.bone_end(Space::World, "Head");
-        let head_start = self.skeleton.bone_start(Space::World, "Head");
-        let head_offset = (head_end - head_start) * 0.5;
-        renderer.draw_circle(head_start + head_offset, 4.0, 0x00d0_d0d0);
-
         // Special weapon handling
         let recoil = self.recoil;
+        let aim_direction = (aim_target - self.compute_shoulder()).angle();
         if let Some(weapon) = self.get_accessory_mut::<Weapon>("Weapon") {
-            weapon.set_aim_direction(direction);
+            weapon.set_aim_direction(aim_direction);
             weapon.set_recoil(recoil);
         }
 
@@ -638,6 +817,8 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
             return;
         }
 
+        self.get_up_timer = (self.get_up_timer - dt).max(0.0);
+
         let velocity = self.state.velocity();
 
         // Compression
@@ -690,14 +871,171 @@ impl<T: StickFigureState, R: Renderer + 'static, C: Collider + 'static> StickFig
 
     }
 
-    fn compute_view_horizon_distance(&self) -> f32 {
-        let shoulder = self.skeleton.bone_end(Space::Local, "Back");
-        let aim = shoulder + Angle::offset(
-            self.state.direction(),
-            self.config.line_of_sight_length
-        );
-        aim.y - shoulder.y
+    fn update_accessory_goals(&mut self) {
+
+        let iks: Vec<(&'static str, IKTarget, bool)> = self.accessories.values().filter_map(|accessory| {
+            accessory.get_iks(&self.skeleton)
+
+        }).flatten().collect();
+
+        // Blend out goals of accessories which no longer provide them
+        for goal in self.accessory_goals.values() {
+            self.skeleton.set_ik_goal_target(*goal, None);
+        }
+
+        for (bone, target, positive) in iks {
+
+            let goal = if let Some(goal) = self.accessory_goals.get(bone) {
+                *goal
+
+            } else if let Ok(goal) = self.skeleton.add_ik_goal(bone, positive, 1) {
+                let duration = self.config.ik_blend_duration;
+                self.skeleton.set_ik_goal_blend(goal, duration, duration);
+
+                // Keep hands on weapons during recoil and at extreme angles
+                self.skeleton.set_ik_goal_reach(goal, IKReach::Stretch(self.config.ik_max_stretch));
+                self.accessory_goals.insert(bone, goal);
+                goal
+
+            } else {
+                continue;
+            };
+
+            self.skeleton.set_ik_goal_target(goal, Some(target));
+
+        }
+
+    }
+
+    // Momentum with which accessories are dropped
+    fn accessory_force(&self) -> Vec2 {
+        let facing = Angle::facing(self.state.direction() + D90).to_vec();
+        (self.state.force().scale(facing) + self.state.velocity()) * 0.5
+    }
+
+    fn compute_shoulder(&self) -> Vec2 {
+        self.bones.back.map_or(self.skeleton.world_offset(), |back| {
+            self.skeleton.bone_end_by_id(Space::World, back)
+        })
+    }
+
+    fn compute_aim_target(&self) -> Vec2 {
+        self.aim_target.unwrap_or_else(|| {
+            self.compute_shoulder() + Angle::offset(
+                self.state.direction(),
+                self.config.line_of_sight_length
+            )
+        })
+    }
+
+}
+
+
+// Severed Limbs --------------------------------------------------------------
+pub struct StickFigureLimb {
+    bone: String,
+    ragdoll: Ragdoll,
+    scale: f32,
+    ragdoll_timer: f32
+}
+
+impl StickFigureLimb {
+
+    pub fn bone(&self) -> &str {
+        &self.bone
+    }
+
+    // World space position at which the limb was cut off
+    pub fn stump(&self) -> Vec2 {
+        self.ragdoll.constraint_points(&self.bone).1
+    }
+
+    pub fn world_bounds(&self) -> (Vec2, Vec2) {
+        self.ragdoll.bounds()
+    }
+
+    pub fn at_rest(&self) -> bool {
+        self.ragdoll.at_rest()
+    }
+
+    pub fn apply_force(&mut self, origin: Vec2, force: Vec2, width: f32) {
+        self.ragdoll.apply_force(origin, force, width);
+        self.ragdoll.activate();
+    }
+
+    pub fn step<C: Collider>(&mut self, dt: f32, gravity: Vec2, collider: &C) {
+        self.ragdoll_timer += dt;
+        let ragdoll_timer = self.ragdoll_timer;
+        self.ragdoll.step(dt, gravity, |p| {
+            if let Some((pos, _, vertical)) = collider.world(p.position) {
+                p.position = pos;
+                if ragdoll_timer > 1.0 && vertical == 1 {
+                    p.set_invmass(0.5);
+                }
+            }
+        });
+    }
+
+    pub fn draw<R: Renderer>(&self, renderer: &mut R) {
+        let scale = self.scale;
+        self.ragdoll.visit(|start, end, name| {
+            draw_bone(renderer, name, start, end, scale);
+        });
     }
 
 }
 
+
+// Helpers --------------------------------------------------------------------
+fn draw_bone<R: Renderer>(renderer: &mut R, name: &str, start: Vec2, end: Vec2, scale: f32) {
+    if name == "Head" {
+        renderer.draw_circle(start + (end - start) * 0.5, 4.0 * scale, 0x00d0_d0d0);
+
+    } else if name.starts_with("R.") {
+        renderer.draw_line(start, end, 0x0080_8080);
+
+    } else if name != "Root" {
+        renderer.draw_line(start, end, 0x00d0_d0d0);
+    }
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn test_animations_within_bone_limits() {
+
+        let animations: [&AnimationData; 6] = [
+            &IDLE_ANIMATION,
+            &GET_UP_FACE_UP_ANIMATION,
+            &GET_UP_FACE_DOWN_ANIMATION,
+            &JUMP_ANIMATION,
+            &RUN_ANIMATION,
+            &WALK_BACKWARDS_ANIMATION
+        ];
+
+        for animation in &animations {
+            for (_, bones) in &animation.key_frames {
+                for &(ref name, angle) in bones {
+
+                    // Key frames are applied on top of the rest angle
+                    let &(_, (_, _, rest, _, min, max)) = DEFAULT_FIGURE_SKELETON.bones.iter().find(|b| {
+                        &b.0 == name
+
+                    }).expect("Animated bone is not part of the skeleton");
+
+                    let angle = rest + angle;
+                    assert!(min.is_none_or(|min| angle >= min), "{} below limit: {}", name, angle);
+                    assert!(max.is_none_or(|max| angle <= max), "{} above limit: {}", name, angle);
+
+                }
+            }
+        }
+
+    }
+
+}
@@ -12,7 +12,7 @@ use std::f32::consts::PI;
 
 
 // Internal Dependencies ------------------------------------------------------
-use ::{Angle, Vec2, Space, Skeleton, RigidBody, RigidBodyData};
+use ::{Angle, Vec2, Space, Skeleton, RigidBody, RigidBodyData, IKTarget};
 use ::library::{Accessory, Renderer, Collider};
 
 
@@ -89,6 +89,10 @@ impl<R: Renderer, C: Collider> Accessory<R, C> for Weapon {
         self.bone = bone;
     }
 
+    fn bone(&self) -> &'static str {
+        self.bone
+    }
+
     fn attach(&mut self, _: &Skeleton) {
         self.ragdoll_duration = 0.0;
         self.has_ragdoll = false;
@@ -110,26 +114,37 @@ impl<R: Renderer, C: Collider> Accessory<R, C> for Weapon {
         self.rigid.apply_dynamic_force(force);
     }
 
-    fn get_iks(&self, skeleton: &Skeleton) -> Option<Vec<(&'static str, Vec2, bool)>> {
+    fn get_iks(&self, skeleton: &Skeleton) -> Option<Vec<(&'static str, IKTarget, bool)>> {
         if self.has_ragdoll {
             None
 
         } else {
-            let shoulder = skeleton.bone_end(Space::Animation, self.bone);
-            Some(self.rigid.iks_static(shoulder))
+            // Convert into animation space, skeletons always face to the
+            // right internally. The offsets are relative to the shoulder so
+            // the hands follow it during the skeleton's next step.
+            let shoulder = skeleton.attachment_point(Space::World, self.bone).ok()?;
+            let origin = skeleton.local_to_animation(skeleton.to_local(shoulder));
+            Some(self.rigid.iks_static(shoulder).into_iter().filter_map(|(bone, p, positive)| {
+                let offset = skeleton.local_to_animation(skeleton.to_local(p)) - origin;
+                skeleton.attachment_target(self.bone, offset).map(|target| (bone, target, positive))
+
+            }).collect())
         }
     }
 
     fn fixate(&mut self, skeleton: &Skeleton) {
-        if !self.has_ragdoll {
+        if self.has_ragdoll {
+            return;
+        }
+
+        if let Ok(shoulder) = skeleton.attachment_point(Space::World, self.bone) {
 
-            let shoulder = skeleton.bone_end(Space::World, self.bone);
             let facing = Angle::facing(self.direction + PI * 0.5).to_vec();
 
             self.rigid.step_static(
                 shoulder,
                 Vec2::new(-self.recoil, 0.0),
-                facing.flipped(),
+                facing.flipped() * skeleton.transform().scale,
                 self.direction
             );
 
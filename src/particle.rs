@@ -7,6 +7,10 @@
 // except according to those terms.
 
 
+// STD Dependencies -----------------------------------------------------------
+use std::collections::HashMap;
+
+
 // Internal Dependencies ------------------------------------------------------
 use super::Vec2;
 
@@ -24,9 +28,16 @@ pub trait Constraint {
     fn first_particle(&self) -> usize;
     fn second_particle(&self) -> usize;
     fn solve(&self, &mut [Particle]) {}
+    // Moves the constraint over to new particle indices, fails if any of its
+    // particles is missing from the map
+    fn remap_particles(&mut self, map: &HashMap<usize, usize>) -> bool;
     fn visual(&self) -> bool {
         false
     }
+    // Particles whose distance is actually being solved
+    fn solved_particles(&self) -> (usize, usize) {
+        (self.first_particle(), self.second_particle())
+    }
 }
 
 
@@ -79,6 +90,17 @@ impl Constraint for StickConstraint {
         self.b
     }
 
+    fn remap_particles(&mut self, map: &HashMap<usize, usize>) -> bool {
+        if let (Some(a), Some(b)) = (map.get(&self.a), map.get(&self.b)) {
+            self.a = *a;
+            self.b = *b;
+            true
+
+        } else {
+            false
+        }
+    }
+
     fn solve(&self, particles: &mut [Particle]) {
 
         let i1 = particles[self.a].inv_mass;
@@ -159,6 +181,22 @@ impl Constraint for AngularConstraint {
         self.j
     }
 
+    fn solved_particles(&self) -> (usize, usize) {
+        (self.p, self.e)
+    }
+
+    fn remap_particles(&mut self, map: &HashMap<usize, usize>) -> bool {
+        if let (Some(p), Some(e), Some(j)) = (map.get(&self.p), map.get(&self.e), map.get(&self.j)) {
+            self.p = *p;
+            self.e = *e;
+            self.j = *j;
+            true
+
+        } else {
+            false
+        }
+    }
+
     fn solve(&self, particles: &mut [Particle]) {
 
         let i1 = particles[self.p].inv_mass;
@@ -0,0 +1,119 @@
+// Copyright (c) 2017 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Internal Dependencies ------------------------------------------------------
+use super::{Angle, Vec2};
+
+
+// Skeleton Pose Abstraction --------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pose {
+    // Bone angles relative to their parents, indexed by bone
+    pub angles: Vec<f32>,
+    // Offset of the root bones in animation space
+    pub offset: Vec2
+}
+
+impl Pose {
+
+    pub fn new(angles: Vec<f32>, offset: Vec2) -> Self {
+        Self {
+            angles,
+            offset
+        }
+    }
+
+    pub fn lerp(&self, other: &Pose, t: f32) -> Pose {
+        Pose {
+            angles: self.angles.iter().zip(other.angles.iter()).map(|(a, b)| {
+                Angle::lerp(*a, *b, t)
+
+            }).collect(),
+            offset: self.offset + (other.offset - self.offset) * t
+        }
+    }
+
+    pub fn additive(&self, additive: &Pose, weight: f32) -> Pose {
+        Pose {
+            angles: self.angles.iter().zip(additive.angles.iter()).map(|(a, b)| {
+                a + b * weight
+
+            }).collect(),
+            offset: self.offset + additive.offset * weight
+        }
+    }
+
+    pub fn difference(&self, base: &Pose) -> Pose {
+        Pose {
+            angles: self.angles.iter().zip(base.angles.iter()).map(|(a, b)| {
+                let r = a - b;
+                r.sin().atan2(r.cos())
+
+            }).collect(),
+            offset: self.offset - base.offset
+        }
+    }
+
+}
+
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod test {
+
+    use std::f32::consts::PI;
+    use super::{Pose, Vec2};
+
+    fn assert_pose(pose: &Pose, angles: &[f32], offset: Vec2) {
+        assert_eq!(pose.angles.len(), angles.len());
+        for (a, b) in pose.angles.iter().zip(angles.iter()) {
+            assert!((a - b).abs() < 0.0001, "{} != {}", a, b);
+        }
+        assert!((pose.offset - offset).length() < 0.0001);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let a = Pose::new(vec![0.0, 1.0], Vec2::new(0.0, 0.0));
+        let b = Pose::new(vec![1.0, -1.0], Vec2::new(10.0, -4.0));
+        assert_pose(&a.lerp(&b, 0.0), &[0.0, 1.0], Vec2::new(0.0, 0.0));
+        assert_pose(&a.lerp(&b, 0.5), &[0.5, 0.0], Vec2::new(5.0, -2.0));
+        assert_pose(&a.lerp(&b, 1.0), &[1.0, -1.0], Vec2::new(10.0, -4.0));
+    }
+
+    #[test]
+    fn test_lerp_takes_shortest_rotation() {
+        let a = Pose::new(vec![PI - 0.1], Vec2::zero());
+        let b = Pose::new(vec![-PI + 0.1], Vec2::zero());
+        assert_pose(&a.lerp(&b, 0.5), &[PI], Vec2::zero());
+    }
+
+    #[test]
+    fn test_difference_wraps_angles() {
+        let a = Pose::new(vec![PI - 0.1, 0.5], Vec2::new(3.0, 4.0));
+        let b = Pose::new(vec![-PI + 0.1, 0.25], Vec2::new(1.0, 1.0));
+        assert_pose(&a.difference(&b), &[-0.2, 0.25], Vec2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn test_additive_difference() {
+        let base = Pose::new(vec![0.25, -0.5], Vec2::new(1.0, 2.0));
+        let pose = Pose::new(vec![0.75, 0.5], Vec2::new(2.0, 0.0));
+        let delta = pose.difference(&base);
+
+        // Adding the full difference back onto the base restores the pose...
+        assert_pose(&base.additive(&delta, 1.0), &[0.75, 0.5], Vec2::new(2.0, 0.0));
+
+        // ...while a partial weight only applies part of it
+        assert_pose(&base.additive(&delta, 0.5), &[0.5, 0.0], Vec2::new(1.5, 1.0));
+        assert_pose(&base.additive(&delta, 0.0), &[0.25, -0.5], Vec2::new(1.0, 2.0));
+    }
+
+}
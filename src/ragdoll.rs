@@ -9,7 +9,7 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::collections::{HashMap, HashSet};
-use super::{Constraint, StickConstraint, Particle, ParticleSystem, Vec2};
+use super::{Constraint, ConstraintType, StickConstraint, Particle, ParticleSystem, Vec2};
 
 
 // Skeleton Ragdoll Abstraction -----------------------------------------------
@@ -52,20 +52,47 @@ impl Ragdoll {
         self.bounds
     }
 
+    pub fn constraint_index(&self, name: &str) -> Option<usize> {
+        self.constraint_name_map.get(name).cloned()
+    }
+
     pub fn constraint_points(&self, name: &str) -> (Vec2, Vec2) {
         if let Some(index) = self.constraint_name_map.get(name) {
-            let c = &self.constraints[*index];
-            (
-                self.joints[c.first_particle()].position,
-                self.joints[c.second_particle()].position
-            )
+            self.constraint_points_by_index(*index)
 
         } else {
             (Vec2::zero(), Vec2::zero())
         }
     }
 
+    pub fn joint_position(&self, index: usize) -> Vec2 {
+        self.joints[index].position
+    }
+
+    pub fn joint_mut(&mut self, index: usize) -> &mut Particle {
+        &mut self.joints[index]
+    }
+
+    pub fn constraint_points_by_index(&self, index: usize) -> (Vec2, Vec2) {
+        let c = &self.constraints[index];
+        (
+            self.joints[c.first_particle()].position,
+            self.joints[c.second_particle()].position
+        )
+    }
+
     // Others -----------------------------------------------------------------
+    pub fn activate(&mut self) {
+        self.steps_until_rest = 10;
+    }
+
+    pub fn translate(&mut self, offset: Vec2) {
+        for joint in &mut self.joints {
+            joint.position = joint.position + offset;
+            joint.prev_position = joint.prev_position + offset;
+        }
+    }
+
     pub fn step<C: Fn(&mut Particle)>(&mut self, dt: f32, gravity: Vec2, collider: C) {
 
         if self.steps_until_rest == 0 {
@@ -98,6 +125,13 @@ impl Ragdoll {
         }
     }
 
+    pub fn visit_helpers<C: FnMut(Vec2, Vec2, ConstraintType)>(&self, mut callback: C) {
+        for c in self.constraints.iter().filter(|c| !c.visual()) {
+            let (a, b) = c.solved_particles();
+            callback(self.joints[a].position, self.joints[b].position, c.typ());
+        }
+    }
+
     // Forces -----------------------------------------------------------------
     pub fn apply_force(&mut self, local_origin: Vec2, force: Vec2, width: f32) {
 
@@ -123,14 +157,75 @@ impl Ragdoll {
 
     }
 
-    pub fn split_bone_from_parent(&mut self, name: &str) {
-        self.split_off_joint(name, None);
+    pub fn split_bone_from_parent(&mut self, name: &str) -> Option<usize> {
+        self.split_off_joint(name, None)
+    }
+
+    // Cuts a bone in two at a fraction of its length from its start
+    pub fn split_bone_at(&mut self, name: &str, at_length: f32) -> Option<usize> {
+        self.split_off_joint(name, Some(at_length))
+    }
+
+    pub fn add_constraint(&mut self, constraint: Box<dyn Constraint>) {
+        self.constraints.push(constraint);
+        self.rebuild_constraints();
+    }
+
+    pub fn sever(&mut self, name: &str, at_length: Option<f32>) -> Option<Ragdoll> {
+
+        let end = self.constraints[self.constraint_index(name)?].first_particle();
+
+        // Remove the constraint from its parent socket or cut it first...
+        let socket = self.split_off_joint(name, at_length)?;
+
+        // ...after which everything still connected to its end belongs to the piece
+        let ci = self.constraint_index(name)?;
+        let mut piece_points = HashSet::new();
+        self.find_points_behind_constraint(ci, end, &mut piece_points);
+        piece_points.insert(socket);
+
+        // Split the joints into two sets with their own indices
+        let mut piece_map = HashMap::new();
+        let mut piece_joints = Vec::new();
+        let mut joint_map = HashMap::new();
+        let mut joints = Vec::new();
+        for (index, joint) in self.joints.drain(..).enumerate() {
+            if piece_points.contains(&index) {
+                piece_map.insert(index, piece_joints.len());
+                piece_joints.push(joint);
+
+            } else {
+                joint_map.insert(index, joints.len());
+                joints.push(joint);
+            }
+        }
+
+        // Move constraints along with their joints, dropping the ones which
+        // still link both sets
+        let mut piece_constraints = Vec::new();
+        let mut constraints = Vec::new();
+        for mut c in self.constraints.drain(..) {
+            if c.remap_particles(&piece_map) {
+                piece_constraints.push(c);
+
+            } else if c.remap_particles(&joint_map) {
+                constraints.push(c);
+            }
+        }
+
+        self.joints = joints;
+        self.constraints = constraints;
+        self.rebuild_constraints();
+        self.activate();
+
+        Some(Ragdoll::new(piece_joints, piece_constraints))
+
     }
 
     // Internal ---------------------------------------------------------------
-    fn split_off_joint(&mut self, name: &str, at_length: Option<f32>) {
+    fn split_off_joint(&mut self, name: &str, at_length: Option<f32>) -> Option<usize> {
 
-        let ci = self.constraint_name_map[name];
+        let ci = *self.constraint_name_map.get(name)?;
         let (end, start) = {
             let constraint = &self.constraints[ci];
             (
@@ -158,11 +253,53 @@ impl Ragdoll {
             !is_crossing
         });
 
+        // Removing constraints might have shifted the index of the split one
+        let ci = self.constraints.iter().position(|c| c.name() == name)?;
+
         // Split constraint at length...
-        if let Some(_) = at_length {
+        let joint = if let Some(at_length) = at_length {
+
+            // We insert a new joint at the cut for both sides, keeping the
+            // motion of the constraint at that point
+            let t = at_length.clamp(0.0, 1.0);
+            let (a, b) = (self.joints[start], self.joints[end]);
+            let mut cut = b;
+            cut.position = a.position + (b.position - a.position) * t;
+            cut.prev_position = a.prev_position + (b.prev_position - a.prev_position) * t;
+
+            let near_index = self.joints.len();
+            let far_index = near_index + 1;
+            self.joints.push(cut);
+            self.joints.push(cut);
+
+            // The near side becomes a stump on the parent...
+            let length = (b.position - a.position).length();
+            let mut near = StickConstraint::new(
+                format!("{}.Stump", name),
+                near_index,
+                start,
+                length * t
+            );
+            near.set_visual(true);
+
+            // ...while the far side keeps the name and now starts at the cut
+            let mut far = StickConstraint::new(
+                name.to_string(),
+                end,
+                far_index,
+                length * (1.0 - t)
+            );
+            far.set_visual(true);
+
+            self.constraints[ci] = Box::new(far);
+            self.constraints.push(Box::new(near));
 
-            // TODO
+            Some(far_index)
 
+        // ...keep sockets which were already split off from their parent,
+        // along with the limits which are attached to them...
+        } else if right_points.len() == 1 {
+            Some(start)
 
         // ...or remove joint from parent socket...
         } else {
@@ -185,11 +322,15 @@ impl Ragdoll {
             // Finally replace old constraint with the new one that using the duplicated point
             self.constraints[ci] = Box::new(c);
 
-        }
+            Some(new_index)
+
+        };
 
         // Rebuild joint constraint map
         self.rebuild_constraints();
 
+        joint
+
     }
 
     fn rebuild_constraints(&mut self) {
@@ -0,0 +1,24 @@
+// Copyright (c) 2017 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Internal Dependencies ------------------------------------------------------
+use ::Vec2;
+
+
+// Traits ---------------------------------------------------------------------
+pub trait Renderer {
+    fn dt(&self) -> f32;
+    fn time(&self) -> f32;
+    fn draw_line(&mut self, start: Vec2, end: Vec2, color: u32);
+    fn draw_circle(&mut self, c: Vec2, r: f32, color: u32);
+    fn draw_rect(&mut self, tr: Vec2, bl: Vec2, color: u32);
+    // Optional, renderers without text support simply skip labels
+    fn draw_text(&mut self, _: Vec2, _: &str, _: u32) {}
+}
+
@@ -99,9 +99,7 @@ impl RigidBody {
             let p = Vec2::new(x, y);
             (
                 bone,
-                // Scale one for rotation, then scale back to work with skeletons
-                // which always face to the right internally
-                (p + self.offset).scale(self.scale).rotate(self.angle).scale(self.scale.flipped()) + offset,
+                (p + self.offset).scale(self.scale).rotate(self.angle) + offset,
                 positive
             )
 
@@ -8,117 +8,439 @@
 
 
 // STD Dependencies -----------------------------------------------------------
+use std::fmt;
 use std::f32::EPSILON;
-use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 
 // Internal Dependencies ------------------------------------------------------
-use super::{Angle, Space, Vec2, f32_equals};
+use super::{Angle, Space, Transform, Vec2, f32_equals};
 use super::animation::{Animator, AnimatorBuilder, AnimationFrameBone};
+use super::pose::Pose;
+use super::data::{self, DataError};
 use super::{
-    Constraint, AngularConstraint, StickConstraint, Ragdoll, Particle
+    Constraint, ConstraintType, AngularConstraint, StickConstraint, Ragdoll, Particle, Renderer
 };
 
 
 // Types ----------------------------------------------------------------------
+#[derive(Debug, Deserialize)]
 pub enum SkeletalConstraint {
-    Stick(&'static str, &'static str),
-    Angular(&'static str, &'static str, &'static str, f32, f32),
+    Stick(String, String),
+    Angular(String, String, String, f32, f32),
 }
 
 type SkeletalBoneDescription = (
-    // Parent, length, angle, ragdoll_inv_mass
-    &'static str, f32, f32, f32, Option<f32>, Option<f32>
+    // Parent, length, angle, ragdoll_inv_mass, min_angle, max_angle
+    //
+    // Angle limits are relative to the parent bone, with positive angles
+    // rotating clockwise in animation space.
+    String, f32, f32, f32, Option<f32>, Option<f32>
 );
-type SkeletalBone = (&'static str, SkeletalBoneDescription);
-type RagdollBoneLink = (&'static str, &'static str);
+type SkeletalBone = (String, SkeletalBoneDescription);
+type SkeletalSocketDescription = (
+    // Bone, offset_x, offset_y, angle
+    //
+    // Offsets are relative to the bone's end point, with the x axis
+    // pointing along the bone. The angle is relative to the bone's angle.
+    String, f32, f32, f32
+);
+type SkeletalSocket = (String, SkeletalSocketDescription);
+type SkeletalHitbox = (
+    // Bone, radius
+    //
+    // Bones are hit tested as capsules around their start and end points,
+    // bones without a hitbox are ignored.
+    String, f32
+);
+type RagdollBoneLink = (String, String);
+type RagdollSplitJoint = (
+    // Bone, (left_angle, right_angle)
+    //
+    // The limits constrain the bone's children around the newly created
+    // socket joint, just like an angular constraint would.
+    String, Option<(f32, f32)>
+);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BoneId(usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct LookAtId(usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct IKGoalId(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IKTarget {
+    // A fixed point in the given space
+    Point(Space, Vec2),
+    // An offset in animation space from a point fixed to a bone, which is
+    // given in the bone's frame relative to its end. Resolved against the
+    // current pose, so the target moves along with the bone.
+    Attached(BoneId, Vec2, Vec2),
+    // The bone's animated end point pushed out by the step's collider and
+    // following the ground's slope when probing is enabled
+    Ground
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum IKReach {
+    // Keep the animated pose for targets out of reach
+    #[default]
+    Keep,
+    // Fully extend towards targets out of reach
+    Extend,
+    // Stretch the bones up to the given ratio of their length, then extend
+    Stretch(f32)
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct IKOptions {
+    // Picks the bend direction which places the joint closest to it,
+    // falling back to `positive` without one
+    pub pole: Option<Vec2>,
+    pub reach: IKReach,
+    pub positive: bool,
+    // Whether the target and pole are given in local instead of animation space
+    pub transformed: bool
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoneHit {
+    pub bone: BoneId,
+    pub point: Vec2,
+    // Distance from the bone's axis for points, or along the ray / segment
+    pub distance: f32
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RagdollFacing {
+    Up,
+    Down
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DebugDrawOptions {
+    pub joints: bool,
+    pub names: bool,
+    pub limits: bool,
+    pub ik_targets: bool,
+    pub sockets: bool,
+    pub bounds: bool,
+    // Non-visual helper constraints, only drawn in ragdoll mode
+    pub constraints: bool
+}
+
+impl Default for DebugDrawOptions {
+    fn default() -> Self {
+        Self {
+            joints: true,
+            names: true,
+            limits: true,
+            ik_targets: true,
+            sockets: true,
+            bounds: true,
+            constraints: true
+        }
+    }
+}
+
+
+// Statics --------------------------------------------------------------------
+const IK_LIMIT_TOLERANCE: f32 = 0.01;
+const IK_REACH_TOLERANCE: f32 = 0.0001;
+const GROUND_PROBE_STEP: f32 = 1.0;
+const GROUND_NORMAL_OFFSET: f32 = 2.0;
+const GROUND_CONTACT_HEIGHT: f32 = 4.0;
+const VELOCITY_HISTORY_FRAMES: usize = 4;
+const TELEPORT_DISTANCE: f32 = 32.0;
+const DEBUG_JOINT_RADIUS: f32 = 1.0;
+const DEBUG_MARKER_SIZE: f32 = 3.0;
+const DEBUG_LIMIT_RADIUS: f32 = 6.0;
+const DEBUG_LIMIT_SEGMENTS: usize = 8;
+const DEBUG_JOINT_COLOR: u32 = 0x00ff_ff00;
+const DEBUG_NAME_COLOR: u32 = 0x00c0_c0c0;
+const DEBUG_LIMIT_COLOR: u32 = 0x0000_a0a0;
+const DEBUG_TARGET_COLOR: u32 = 0x0000_ff00;
+const DEBUG_LOCKED_COLOR: u32 = 0x00ff_8000;
+const DEBUG_SOCKET_COLOR: u32 = 0x00ff_00ff;
+const DEBUG_STICK_COLOR: u32 = 0x0060_60ff;
+const DEBUG_ANGULAR_COLOR: u32 = 0x00ff_6060;
+const DEBUG_ACTIVE_COLOR: u32 = 0x00ff_0000;
+const DEBUG_REST_COLOR: u32 = 0x0000_c0f0;
+
+
+// Errors ---------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkeletonError {
+    NoRoot,
+    MultipleRoots(String, String),
+    MissingParent(String, String),
+    Cycle(String),
+    DuplicateBone(String),
+    InvalidLimits(String),
+    UnknownConstraintBone(String),
+    UnknownRagdollBone(String),
+    UnknownSocketBone(String),
+    DuplicateSocket(String),
+    UnknownHitboxBone(String),
+    UnknownBone(String),
+    NoParent(String),
+    InvalidChain(String, String)
+}
+
+impl fmt::Display for SkeletonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SkeletonError::NoRoot => write!(f, "Skeleton has no root bone"),
+            SkeletonError::MultipleRoots(ref a, ref b) => write!(f, "Skeleton has multiple root bones \"{}\" and \"{}\"", a, b),
+            SkeletonError::MissingParent(ref bone, ref parent) => write!(f, "Bone \"{}\" references unknown parent \"{}\"", bone, parent),
+            SkeletonError::Cycle(ref bone) => write!(f, "Bone \"{}\" is part of a parent cycle", bone),
+            SkeletonError::DuplicateBone(ref bone) => write!(f, "Bone \"{}\" is defined more than once", bone),
+            SkeletonError::InvalidLimits(ref bone) => write!(f, "Bone \"{}\" has limits outside of -PI to PI or crossing it", bone),
+            SkeletonError::UnknownConstraintBone(ref bone) => write!(f, "Constraint references unknown bone \"{}\"", bone),
+            SkeletonError::UnknownRagdollBone(ref bone) => write!(f, "Ragdoll references unknown bone \"{}\"", bone),
+            SkeletonError::UnknownSocketBone(ref bone) => write!(f, "Socket references unknown bone \"{}\"", bone),
+            SkeletonError::DuplicateSocket(ref socket) => write!(f, "Socket \"{}\" is defined more than once", socket),
+            SkeletonError::UnknownHitboxBone(ref bone) => write!(f, "Hitbox references unknown bone \"{}\"", bone),
+            SkeletonError::UnknownBone(ref bone) => write!(f, "Unknown bone \"{}\"", bone),
+            SkeletonError::NoParent(ref bone) => write!(f, "Bone \"{}\" has no parent", bone),
+            SkeletonError::InvalidChain(ref tip, ref root) => write!(f, "Bone \"{}\" is not a descendant of \"{}\"", tip, root)
+        }
+    }
+}
+
+impl Error for SkeletonError {}
+
+
+// IK Solvers -----------------------------------------------------------------
+#[derive(Debug, Copy, Clone)]
+pub enum IKSolver {
+    FABRIK { iterations: usize, tolerance: f32 },
+    CCD { iterations: usize, tolerance: f32 }
+}
+
+impl Default for IKSolver {
+    fn default() -> Self {
+        IKSolver::FABRIK {
+            iterations: 10,
+            tolerance: 0.1
+        }
+    }
+}
 
 
 // Skeleton Data Abstraction --------------------------------------------------
+#[derive(Debug, Deserialize)]
 pub struct SkeletalData {
     pub bones: Vec<SkeletalBone>,
+    #[serde(default)]
     pub ragdoll_parents: Vec<RagdollBoneLink>,
-    pub constraints: Vec<SkeletalConstraint>
+    #[serde(default)]
+    pub ragdoll_splits: Vec<RagdollSplitJoint>,
+    #[serde(default)]
+    pub constraints: Vec<SkeletalConstraint>,
+    #[serde(default)]
+    pub sockets: Vec<SkeletalSocket>,
+    #[serde(default)]
+    pub hitboxes: Vec<SkeletalHitbox>
 }
 
 impl SkeletalData {
 
-    fn to_internal_bones(&'static self) -> Vec<Bone> {
+    pub fn from_ron(text: &str) -> Result<Arc<Self>, DataError> {
+        data::from_ron(text)
+    }
 
-        // Generate initial bones
-        let mut bones: Vec<Bone> = self.bones.iter().enumerate().map(|(index, bone)| {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Arc<Self>, DataError> {
+        data::load_ron(path)
+    }
 
-            // Find parent bone index
-            let mut parent = 255;
-            for (i, p) in self.bones.iter().enumerate() {
-                if p.0 == (bone.1).0
-                    // Don't set the root bone as its own parent
-                    && p.0 != bone.0 {
-                    parent = i;
-                    break;
+    pub fn validate(&self) -> Result<(), SkeletonError> {
+
+        let mut names = HashMap::with_capacity(self.bones.len());
+        for (index, bone) in self.bones.iter().enumerate() {
+            if names.insert(bone.0.as_str(), index).is_some() {
+                return Err(SkeletonError::DuplicateBone(bone.0.clone()));
+            }
+
+            // Limits are clamped within -PI to PI, so ranges wrapping around
+            // it cannot be expressed
+            let (min, max) = ((bone.1).4.unwrap_or(-PI), (bone.1).5.unwrap_or(PI));
+            if min < -PI || max > PI || min > max {
+                return Err(SkeletonError::InvalidLimits(bone.0.clone()));
+            }
+        }
+
+        // There must be exactly one root bone, which is its own parent...
+        let mut roots = self.bones.iter().filter(|bone| bone.0 == (bone.1).0);
+        match (roots.next(), roots.next()) {
+            (None, _) => return Err(SkeletonError::NoRoot),
+            (Some(a), Some(b)) => return Err(SkeletonError::MultipleRoots(a.0.clone(), b.0.clone())),
+            _ => {}
+        }
+
+        // ...and every bone needs to lead up to it
+        for bone in &self.bones {
+            let mut current = bone;
+            let mut steps = 0;
+            while current.0 != (current.1).0 {
+                current = if let Some(index) = names.get((current.1).0.as_str()) {
+                    &self.bones[*index]
+
+                } else {
+                    return Err(SkeletonError::MissingParent(current.0.clone(), (current.1).0.clone()));
+                };
+
+                steps += 1;
+                if steps > self.bones.len() {
+                    return Err(SkeletonError::Cycle(bone.0.clone()));
                 }
             }
+        }
+
+        for constraint in &self.constraints {
+            let bones = match *constraint {
+                SkeletalConstraint::Stick(ref a, ref b) => vec![a, b],
+                SkeletalConstraint::Angular(ref a, ref b, ref c, _, _) => vec![a, b, c]
+            };
+            for name in bones {
+                if !names.contains_key(name.as_str()) {
+                    return Err(SkeletonError::UnknownConstraintBone(name.clone()));
+                }
+            }
+        }
+
+        for (bone, parent) in &self.ragdoll_parents {
+            for name in &[bone, parent] {
+                if !names.contains_key(name.as_str()) {
+                    return Err(SkeletonError::UnknownRagdollBone(name.to_string()));
+                }
+            }
+        }
+
+        for (name, _) in &self.ragdoll_splits {
+            if !names.contains_key(name.as_str()) {
+                return Err(SkeletonError::UnknownRagdollBone(name.clone()));
+            }
+        }
+
+        let mut sockets = HashSet::with_capacity(self.sockets.len());
+        for &(ref name, (ref bone, _, _, _)) in &self.sockets {
+            if !names.contains_key(bone.as_str()) {
+                return Err(SkeletonError::UnknownSocketBone(bone.clone()));
+
+            } else if !sockets.insert(name.as_str()) {
+                return Err(SkeletonError::DuplicateSocket(name.clone()));
+            }
+        }
+
+        for (bone, _) in &self.hitboxes {
+            if !names.contains_key(bone.as_str()) {
+                return Err(SkeletonError::UnknownHitboxBone(bone.clone()));
+            }
+        }
+
+        Ok(())
+
+    }
+
+    fn to_internal_bones(&self) -> Result<Vec<Bone>, SkeletonError> {
+
+        let mut names = HashMap::with_capacity(self.bones.len());
+        for (index, bone) in self.bones.iter().enumerate().rev() {
+            names.insert(bone.0.as_str(), index);
+        }
+
+        // Generate initial bones
+        let mut bones: Vec<Bone> = self.bones.iter().enumerate().map(|(index, bone)| {
+
+            // Find parent bone index, the root bone is its own parent
+            let parent = if (bone.1).0 == bone.0 {
+                None
+
+            } else {
+                Some(*names.get((bone.1).0.as_str()).ok_or_else(|| {
+                    SkeletonError::MissingParent(bone.0.clone(), (bone.1).0.clone())
+                })?)
+            };
+
+            // Find hitbox radius
+            let radius = self.hitboxes.iter().find(|h| h.0 == bone.0).map_or(0.0, |h| h.1);
 
             // Find ragdoll parent overrides
-            let mut ragdoll_parent = parent;
-            for &(name, parent) in &self.ragdoll_parents {
-                if name == bone.0 {
-                    for (i, p) in self.bones.iter().enumerate() {
-                        if p.0 == parent {
-                            ragdoll_parent = i;
-                            break;
-                        }
+            let mut ragdoll_parent = None;
+            for (name, parent) in &self.ragdoll_parents {
+                if *name == bone.0 {
+                    if let Some(index) = names.get(parent.as_str()) {
+                        ragdoll_parent = Some(*index);
                     }
                 }
             }
 
-            Bone {
+            Ok(Bone {
                 index: index,
                 parent: parent,
+                effective_parent: parent,
                 ragdoll_parent: ragdoll_parent,
                 children: Vec::new(),
 
                 angle: 0.0,
-                animation_angle: 0.0,
                 offset_angle: 0.0,
 
                 start: Vec2::zero(),
                 end: Vec2::zero(),
 
+                name: bone.0.clone(),
+                length: (bone.1).1,
+                stretch: 1.0,
+                radius,
+                inv_mass: (bone.1).3,
                 min_angle: (bone.1).4,
-                max_angle: (bone.1).5,
-
-                data: bone
-            }
+                max_angle: (bone.1).5
+            })
 
-        }).collect();
+        }).collect::<Result<_, SkeletonError>>()?;
 
         // Collect children
-        let bone_children: Vec<Vec<usize>> = bones.iter().map(|bone| {
-            let mut children = Vec::new();
-            for other in &bones {
-                if other.parent == bone.index
-                    // Don't add the root bone as a child of itself
-                    && other.index != other.parent {
-                    children.push(other.index);
-                }
+        for index in 0..bones.len() {
+            if let Some(parent) = bones[index].parent {
+                bones[parent].children.push(index);
             }
-            children
-
-        }).collect();
+        }
 
-        // Assign children
-        for (children, bone) in bone_children.into_iter().zip(bones.iter_mut()) {
-            bone.children = children;
+        // Skip over zero length parents since their start and end points
+        // coincide, falling back to the top most ancestor if all of them
+        // have zero length
+        for index in 0..bones.len() {
+            let mut effective = bones[index].parent;
+            let mut steps = 0;
+            while let Some(parent) = effective {
+                if bones[parent].length > 0.0 || steps >= bones.len() {
+                    break;
+                }
+                match bones[parent].parent {
+                    Some(next) => effective = Some(next),
+                    None => break
+                }
+                steps += 1;
+            }
+            bones[index].effective_parent = effective;
+            bones[index].ragdoll_parent = bones[index].ragdoll_parent.or(effective);
         }
 
-        bones
+        Ok(bones)
 
     }
 
     fn to_animation_bones(&self) -> Vec<AnimationFrameBone> {
         self.bones.iter().map(|bone| {
-            (bone.0, (bone.1).2)
+            (bone.0.clone(), (bone.1).2)
 
         }).collect()
     }
@@ -136,7 +458,7 @@ impl SkeletalData {
 pub struct Skeleton {
 
     // Base skeleton data
-    data: &'static SkeletalData,
+    data: Arc<SkeletalData>,
 
     // Internal bone structure
     bones: Vec<Bone>,
@@ -144,13 +466,17 @@ pub struct Skeleton {
     // Lookup table for Name -> Index relation
     name_to_index: HashMap<String, usize>,
 
+    // Lookup table for Socket -> (Bone Index, Offset, Angle) relation
+    sockets: HashMap<String, (usize, Vec2, f32)>,
+
     // Iteration indices
     child_first_indices: Vec<usize>,
     child_last_indices: Vec<usize>,
 
-    // World position offset
+    // Facing and world transform
     local_transform: Vec2,
-    world_position: Vec2,
+    transform: Transform,
+    root_offset: Vec2,
     bounds: (Vec2, Vec2),
 
     // Animation offsets, with rest angles as defaults
@@ -160,19 +486,44 @@ pub struct Skeleton {
     animator: Animator,
 
     // Ragdoll
-    ragdoll: Option<Ragdoll>
+    ragdoll: Option<Ragdoll>,
+    ragdoll_constraints: Vec<Option<usize>>,
+    ragdoll_weights: Vec<f32>,
+    partial_ragdoll: Option<PartialRagdoll>,
+    recovery: Option<RagdollRecovery>,
+
+    // Bones which were cut off into ragdolls of their own, cut bones keep a
+    // stump of the given fraction of their length
+    severed: Vec<bool>,
+    stumps: Vec<Option<f32>>,
+
+    // World space joint positions of the last animated steps along with
+    // their durations, used to track joint velocities
+    joint_history: VecDeque<(f32, Vec<Vec2>)>,
+
+    // Look at constraints
+    look_ats: Vec<LookAt>,
+
+    // IK goals
+    ik_goals: Vec<IKGoal>,
+
+    // Foot placement on uneven ground
+    ground_probe_height: f32,
+    ground_max_drop: f32,
+    ground_offset: Vec2
 
 }
 
 impl Skeleton {
 
-    pub fn new(data: &'static SkeletalData) -> Self {
+    pub fn try_new(data: Arc<SkeletalData>) -> Result<Self, SkeletonError> {
+        data.validate()?;
 
         // Internal Data Structures
-        let bones = data.to_internal_bones();
+        let bones = data.to_internal_bones()?;
 
         // Lookups
-        let root = vec![0];
+        let roots: Vec<usize> = bones.iter().filter(|b| b.parent.is_none()).map(|b| b.index).collect();
         let mut name_to_index = HashMap::with_capacity(bones.len());
         let mut child_first_indices: Vec<usize> = Vec::with_capacity(bones.len());
         let mut child_last_indices: Vec<usize> = Vec::with_capacity(bones.len());
@@ -181,40 +532,73 @@ impl Skeleton {
             name_to_index.insert(b.name().to_string(), b.index);
         }
 
-        Skeleton::visit_bones(&bones[..], &root[..], &mut |bone| {
+        let mut sockets = HashMap::with_capacity(data.sockets.len());
+        for &(ref name, (ref bone, x, y, angle)) in &data.sockets {
+            if let Some(index) = name_to_index.get(bone) {
+                sockets.insert(name.clone(), (*index, Vec2::new(x, y), angle));
+            }
+        }
+
+        Skeleton::visit_bones(&bones[..], &roots[..], &mut |bone| {
             child_first_indices.push(bone.index);
 
         }, true);
 
-        Skeleton::visit_bones(&bones[..], &root[..], &mut |bone| {
+        Skeleton::visit_bones(&bones[..], &roots[..], &mut |bone| {
             child_last_indices.push(bone.index);
 
         }, false);
 
-        Self {
+        let bones_len = bones.len();
+        Ok(Self {
             // Data Structures
-            data: data,
             bones: bones,
             name_to_index: name_to_index,
+            sockets,
             child_first_indices: child_first_indices,
             child_last_indices: child_last_indices,
 
             // Positions
             local_transform: Vec2::new(1.0, 1.0),
-            world_position: Vec2::zero(),
+            transform: Transform::identity(),
+            root_offset: Vec2::zero(),
             bounds: (Vec2::zero(), Vec2::zero()),
 
             // Animations
             bone_rest_angles: data.to_animation_bones(),
             animator: AnimatorBuilder::new().build(),
+            data,
 
             // Ragdoll
-            ragdoll: None
+            ragdoll: None,
+            ragdoll_constraints: Vec::new(),
+            ragdoll_weights: vec![0.0; bones_len],
+            partial_ragdoll: None,
+            recovery: None,
+            severed: vec![false; bones_len],
+            stumps: vec![None; bones_len],
+            joint_history: VecDeque::with_capacity(VELOCITY_HISTORY_FRAMES),
 
-        }
+            // Look at constraints
+            look_ats: Vec::new(),
+
+            // IK goals
+            ik_goals: Vec::new(),
+
+            // Foot placement
+            ground_probe_height: 0.0,
+            ground_max_drop: 0.0,
+            ground_offset: Vec2::zero()
+
+        })
 
     }
 
+    // Panics on invalid data, use `try_new` to handle the error instead
+    pub fn new(data: Arc<SkeletalData>) -> Self {
+        Skeleton::try_new(data).unwrap_or_else(|err| panic!("Invalid skeleton data: {}", err))
+    }
+
 
     // Ragdoll ----------------------------------------------------------------
     pub fn at_rest(&self) -> bool {
@@ -230,102 +614,214 @@ impl Skeleton {
         self.ragdoll.is_some()
     }
 
-    pub fn start_ragdoll(&mut self) {
+    pub fn start_ragdoll(&mut self) -> Result<(), SkeletonError> {
 
-        let particles = self.bones.iter().map(|bone| {
-            bone.to_particle(self.local_transform)
+        // Carry the animated momentum over into the ragdoll before splitting,
+        // so that the socket joints inherit it
+        let mut ragdoll = self.to_ragdoll()?;
+        self.seed_ragdoll_velocities(&mut ragdoll);
+        self.split_ragdoll(&mut ragdoll)?;
+        self.joint_history.clear();
 
-        }).collect();
+        // Severed bones no longer take part in the simulation
+        for bone in &self.bones {
+            let parent_severed = bone.ragdoll_parent.is_some_and(|p| self.severed[p]);
+            if self.severed[bone.index] && !parent_severed {
+                ragdoll.sever(bone.name(), self.stumps[bone.index]);
+            }
+        }
 
-        let mut constraints: Vec<Box<Constraint>> = self.bones.iter().filter_map(|bone| {
-            bone.to_constaint()
+        // Map bones to their visual constraints
+        self.ragdoll_constraints = self.bones.iter().map(|bone| {
+            ragdoll.constraint_index(bone.name())
 
         }).collect();
 
-        // Additional skeletal constraints
-        for constraint in &self.data.constraints {
-            match *constraint {
-                SkeletalConstraint::Stick(parent, child) => {
-                    let parent = self.bone_by_name(parent).unwrap().index();
-                    let child = self.bone_by_name(child).unwrap().index();
-                    let ap = self.bones[parent].end();
-                    let bp = self.bones[child].end();
-                    constraints.push(
-                        Box::new(StickConstraint::new(
-                            format!("s-{}-{}", parent, child),
-                            parent,
-                            child,
-                            (ap - bp).length()
-                        ))
-                    );
-                },
-                SkeletalConstraint::Angular(parent, joint, child, left, right) => {
-                    let parent = self.bone_by_name(parent).unwrap().index();
-                    let joint = self.bone_by_name(joint).unwrap().index();
-                    let child = self.bone_by_name(child).unwrap().index();
+        self.ragdoll = Some(ragdoll);
+        self.partial_ragdoll = None;
+        self.recovery = None;
+        Ok(())
 
-                    let (left, right) = if f32_equals(self.local_transform.x.signum(), -1.0) {
-                        (right, left)
+    }
+
+    pub fn stop_ragdoll(&mut self) {
+        self.ragdoll.take();
+        self.ragdoll_constraints.clear();
+    }
+
+    pub fn recover_from_ragdoll(&mut self, duration: f32) -> Option<RagdollFacing> {
+
+        // Capture the final joint directions in animation space
+        let angles: Vec<Option<f32>> = {
+            let ragdoll = self.ragdoll.as_ref()?;
+            self.bones.iter().map(|bone| {
+                self.ragdoll_constraints[bone.index].and_then(|c| {
+                    let (end, start) = ragdoll.constraint_points_by_index(c);
+                    let dir = self.local_to_animation(end - start);
+                    if dir.length() > f32::EPSILON {
+                        Some(dir.angle())
 
                     } else {
-                        (left, right)
-                    };
+                        None
+                    }
+                })
 
-                    let a = self.bones[child].length();
-                    let b = self.bones[joint].length();
+            }).collect()
+        };
 
-                    let rest_length = (a * a + b * b - 2.0 * a * b * left.cos()).sqrt();
-                    constraints.push(
-                        Box::new(AngularConstraint::new(
-                            format!("a-{}-{}-{}", parent, joint, child),
-                            parent,
-                            child,
-                            joint,
-                            rest_length,
-                            true
-                        ))
-                    );
+        // The first bone with a length is treated as the torso, its front
+        // points to the right of the skeleton in animation space
+        let torso = self.child_last_indices.iter().find(|i| self.bones[**i].length > 0.0).cloned();
+        let facing = torso.and_then(|i| angles[i]).map_or(RagdollFacing::Up, |angle| {
+            let front = self.animation_to_local(Angle::offset(angle + PI * 0.5, 1.0));
+            if front.y < 0.0 {
+                RagdollFacing::Up
 
-                    let rest_length = (a * a + b * b - 2.0 * a * b * right.cos()).sqrt();
-                    constraints.push(
-                        Box::new(AngularConstraint::new(
-                            format!("a-{}-{}-{}", parent, joint, child),
-                            parent,
-                            child,
-                            joint,
-                            rest_length,
-                            false
-                        ))
-                    );
+            } else {
+                RagdollFacing::Down
+            }
+        });
+
+        // Capture where the torso starts, so the body doesn't snap back onto
+        // the animated root position
+        let torso = torso.and_then(|i| {
+            let ragdoll = self.ragdoll.as_ref()?;
+            let c = self.ragdoll_constraints[i]?;
+            Some((i, self.local_to_animation(ragdoll.constraint_points_by_index(c).1)))
+        });
+
+        self.stop_ragdoll();
+        self.recovery = Some(RagdollRecovery {
+            angles,
+            torso,
+            duration,
+            timer: 0.0
+        });
+
+        Some(facing)
 
-                }
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    pub fn set_ragdoll_weight(&mut self, name: &str, weight: f32) -> Result<(), SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        self.set_ragdoll_weight_by_id(id, weight)
+    }
+
+    pub fn set_ragdoll_weight_by_id(&mut self, id: BoneId, weight: f32) -> Result<(), SkeletonError> {
+
+        self.ragdoll_weights[id.0] = weight.clamp(0.0, 1.0);
+
+        // Build the simulation right away so invalid ragdoll data is
+        // reported here instead of during a step
+        if self.partial_ragdoll.is_none() && weight > 0.0 {
+            self.partial_ragdoll = Some(self.to_partial_ragdoll()?);
+        }
+
+        Ok(())
+
+    }
+
+    pub fn ragdoll_weight(&self, id: BoneId) -> f32 {
+        self.ragdoll_weights[id.0]
+    }
+
+    pub fn reset_ragdoll_weights(&mut self) {
+        for weight in &mut self.ragdoll_weights {
+            *weight = 0.0;
+        }
+    }
+
+
+    // Severing ---------------------------------------------------------------
+    pub fn sever(&mut self, name: &str, at_length: Option<f32>) -> Result<Option<Ragdoll>, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        Ok(self.sever_by_id(id, at_length))
+    }
+
+    // Cuts a bone and its descendants off into an independent ragdoll in
+    // world space, or None for root and already severed bones. With a length
+    // the bone is cut at that fraction and leaves a stump behind.
+    pub fn sever_by_id(&mut self, id: BoneId, at_length: Option<f32>) -> Option<Ragdoll> {
+
+        if self.severed[id.0] {
+            return None;
+        }
+
+        let name = self.bones[id.0].name().to_string();
+        let at_length = at_length.map(|l| l.clamp(0.0, 1.0));
+        let mut piece = if let Some(ref mut ragdoll) = self.ragdoll {
+            ragdoll.sever(&name, at_length)
+
+        } else {
+            // Split just like a full ragdoll would, so the piece keeps the
+            // same sockets and limits either way
+            self.to_ragdoll().ok().and_then(|mut ragdoll| {
+                self.seed_ragdoll_velocities(&mut ragdoll);
+                self.split_ragdoll(&mut ragdoll).ok()?;
+                ragdoll.sever(&name, at_length)
+            })
+
+        }?;
+
+        // Mark all bones which went along with the piece
+        for bone in &self.bones {
+            if piece.constraint_index(bone.name()).is_some() {
+                self.severed[bone.index] = true;
             }
         }
+        self.stumps[id.0] = at_length;
 
-        let mut ragdoll = Ragdoll::new(particles, constraints);
-        ragdoll.split_bone_from_parent("L.Leg");
-        ragdoll.split_bone_from_parent("R.Leg");
-        ragdoll.split_bone_from_parent("L.Arm");
-        ragdoll.split_bone_from_parent("R.Arm");
-        self.ragdoll = Some(ragdoll);
+        if let Some(ref ragdoll) = self.ragdoll {
+            self.ragdoll_constraints = self.bones.iter().map(|bone| {
+                ragdoll.constraint_index(bone.name())
+
+            }).collect();
+        }
+
+        piece.translate(self.transform.position);
+        Some(piece)
 
     }
 
-    pub fn stop_ragdoll(&mut self) {
-        self.ragdoll.take();
+    pub fn is_severed(&self, id: BoneId) -> bool {
+        self.severed[id.0]
+    }
+
+    // Whether the bone behind a socket or bone end has been severed
+    pub fn is_attachment_severed(&self, name: &str) -> bool {
+        self.attachment_bone(name).is_some_and(|id| self.severed[id.0])
     }
 
 
     // Offsets & Positions ----------------------------------------------------
     pub fn set_local_transform(&mut self, transform: Vec2) {
         if self.ragdoll.is_none() {
+            // Turning around is not a movement of the joints
+            if transform != self.local_transform {
+                self.joint_history.clear();
+            }
             self.local_transform = transform;
         }
     }
 
     pub fn set_world_offset(&mut self, p: Vec2) {
         if self.ragdoll.is_none() {
-            self.world_position = p;
+            // Respawns and resets are not a movement of the joints either
+            if (p - self.transform.position).length() > TELEPORT_DISTANCE * self.transform.scale {
+                self.reset_velocities();
+            }
+            self.transform.position = p;
+        }
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        if self.ragdoll.is_none() {
+            self.transform = transform;
+            self.transform.scale = transform.scale.max(f32::EPSILON);
         }
     }
 
@@ -334,15 +830,27 @@ impl Skeleton {
     }
 
     pub fn world_offset(&self) -> Vec2 {
-        self.world_position
+        self.transform.position
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
     }
 
     pub fn to_local(&self, w: Vec2) -> Vec2 {
-        w - self.world_position
+        w - self.transform.position
     }
 
     pub fn to_world(&self, p: Vec2) -> Vec2 {
-        p + self.world_position
+        p + self.transform.position
+    }
+
+    pub fn animation_to_local(&self, p: Vec2) -> Vec2 {
+        self.transform.apply_vector(p.scale(self.local_transform))
+    }
+
+    pub fn local_to_animation(&self, p: Vec2) -> Vec2 {
+        self.transform.apply_inverse_vector(p).scale(self.local_transform)
     }
 
     pub fn local_bounds(&self) -> (Vec2, Vec2) {
@@ -350,18 +858,31 @@ impl Skeleton {
             ragdoll.bounds()
 
         } else {
-            (
-                self.bounds.0.scale(self.local_transform),
-                self.bounds.1.scale(self.local_transform)
-            )
+
+            // Bounds of the rotated corners
+            let (min, max) = self.bounds;
+            let corners = [
+                self.animation_to_local(min),
+                self.animation_to_local(Vec2::new(max.x, min.y)),
+                self.animation_to_local(max),
+                self.animation_to_local(Vec2::new(min.x, max.y))
+            ];
+
+            corners.iter().fold((corners[0], corners[0]), |b, c| {
+                (
+                    Vec2::new(b.0.x.min(c.x), b.0.y.min(c.y)),
+                    Vec2::new(b.1.x.max(c.x), b.1.y.max(c.y))
+                )
+            })
+
         }
     }
 
     pub fn world_bounds(&self) -> (Vec2, Vec2) {
         let bounds = self.local_bounds();
         (
-            bounds.0 + self.world_position,
-            bounds.1 + self.world_position
+            bounds.0 + self.transform.position,
+            bounds.1 + self.transform.position
         )
     }
 
@@ -385,25 +906,70 @@ impl Skeleton {
             // Forward animations and calculate animation bone angles
             self.animator.update(dt, &mut self.bone_rest_angles[..]);
 
-            // Reset all bones to the base skeleton angles
+            // Reset all bones to the base skeleton angles and lengths, poses
+            // only last until the next step, including their root offset
+            self.ground_offset = Vec2::zero();
+            self.root_offset = Vec2::zero();
             for i in &self.child_last_indices {
                 let bone = &mut self.bones[*i];
                 bone.angle = self.bone_rest_angles[*i].1;
+                bone.stretch = 1.0;
             }
 
             // Update all bones relative to their parents
             for i in &self.child_last_indices {
                 let values = self.calculate_bone(*i);
                 let mut bone = &mut self.bones[*i];
-                self.bounds.0.x = self.bounds.0.x.min(bone.start.x).min(bone.end.x);
-                self.bounds.0.y = self.bounds.0.y.min(bone.start.y).min(bone.end.y);
-                self.bounds.1.x = self.bounds.1.x.max(bone.start.x).max(bone.end.x);
-                self.bounds.1.y = self.bounds.1.y.max(bone.start.y).max(bone.end.y);
+                if !self.severed[*i] {
+                    self.bounds.0.x = self.bounds.0.x.min(bone.start.x).min(bone.end.x);
+                    self.bounds.0.y = self.bounds.0.y.min(bone.start.y).min(bone.end.y);
+                    self.bounds.1.x = self.bounds.1.x.max(bone.start.x).max(bone.end.x);
+                    self.bounds.1.y = self.bounds.1.y.max(bone.start.y).max(bone.end.y);
+                }
                 bone.set(values);
             }
 
+            // Turn look at chains towards their targets
+            self.step_look_ats(dt);
+
+            // Reach for IK goals in order of their priority
+            self.step_ik_goals(dt, &collider);
+
+            // Blend in physically simulated bones
+            self.step_partial_ragdoll(dt, gravity, collider);
+
+            // Blend from a captured ragdoll pose back into the animation
+            self.step_recovery(dt);
+
+            // Track joint velocities from the final pose
+            self.record_joints(dt);
+
+        }
+
+    }
+
+
+    // Velocities -------------------------------------------------------------
+    pub fn bone_velocity(&self, name: &str) -> Result<Vec2, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        Ok(self.bone_velocity_by_id(id))
+    }
+
+    // World space velocity of a bone's end point, averaged over the last
+    // animated steps and zero while in ragdoll mode
+    pub fn bone_velocity_by_id(&self, id: BoneId) -> Vec2 {
+        match (self.joint_history.front(), self.joint_history.back()) {
+            (Some((_, first)), Some((_, last))) if self.joint_history.len() > 1 => {
+                let duration: f32 = self.joint_history.iter().skip(1).map(|h| h.0).sum();
+                (last[id.0] - first[id.0]) / duration
+            },
+            _ => Vec2::zero()
         }
+    }
 
+    // Forget tracked velocities, e.g. after teleporting the skeleton
+    pub fn reset_velocities(&mut self) {
+        self.joint_history.clear();
     }
 
 
@@ -418,171 +984,1645 @@ impl Skeleton {
 
     pub fn apply_world_force(&mut self, origin: Vec2, force: Vec2, width: f32) {
         let origin = self.to_local(origin);
-        if let Some(ref mut ragdoll) = self.ragdoll {
-            ragdoll.apply_force(origin, force, width);
-        }
+        self.apply_local_force(origin, force, width);
     }
 
     pub fn apply_local_force(&mut self, origin: Vec2, force: Vec2, width: f32) {
         if let Some(ref mut ragdoll) = self.ragdoll {
             ragdoll.apply_force(origin, force, width);
-        }
-    }
-
-
-    // Bones ------------------------------------------------------------------
-    pub fn bone_start(&self, space: Space, name: &str) -> Vec2 {
-        if let Some(ref ragdoll) = self.ragdoll {
-            let start = ragdoll.constraint_points(name).1;
-            match space {
-                Space::World => self.to_world(start),
-                Space::Local => start,
-                Space::Animation => start.scale(self.local_transform)
-            }
-
-        } else if let Some(bone) = self.bone_by_name(name) {
-            let start = bone.start();
-            match space {
-                Space::World => self.to_world(start.scale(self.local_transform)),
-                Space::Local => start.scale(self.local_transform),
-                Space::Animation => start
-            }
 
-        } else {
-            let start = Vec2::zero();
-            match space {
-                Space::World => self.to_world(start),
-                Space::Local | Space::Animation => start
-            }
+        } else if let Some(ref mut partial) = self.partial_ragdoll {
+            partial.ragdoll.apply_force(origin, force, width);
+            partial.ragdoll.activate();
         }
     }
 
-    pub fn bone_end(&self, space: Space, name: &str) -> Vec2 {
-        if let Some(ref ragdoll) = self.ragdoll {
-            let end = ragdoll.constraint_points(name).0;
-            match space {
-                Space::World => self.to_world(end),
-                Space::Local => end,
-                Space::Animation => end.scale(self.local_transform)
-            }
 
-        } else if let Some(bone) = self.bone_by_name(name) {
-            let end = bone.end();
-            match space {
-                Space::World => self.to_world(end.scale(self.local_transform)),
-                Space::Local => end.scale(self.local_transform),
-                Space::Animation => end
-            }
+    // Poses ------------------------------------------------------------------
+    pub fn capture_pose(&self) -> Pose {
+        Pose::new((0..self.bones.len()).map(|i| {
+            self.bones[i].angle - self.parent_angle(i)
 
-        } else {
-            let end = Vec2::zero();
-            match space {
-                Space::World => self.to_world(end),
-                Space::Local | Space::Animation => end
-            }
-        }
+        }).collect(), self.root_offset)
     }
 
-    pub fn apply_bone_ik(&mut self, name: &str, mut target: Vec2, positive: bool, transformed: bool) {
+    // Overrides the animated pose until the next step
+    pub fn apply_pose(&mut self, pose: &Pose) {
 
-        // Ignore setting IKs during ragdoll
+        // Ignore poses during ragdoll
         if self.ragdoll.is_some() {
             return;
         }
 
-        // Transform IK target into animation space
-        if transformed {
-            target = target.scale(self.local_transform);
+        self.root_offset = pose.offset;
+
+        // Bones missing from the pose keep their current angles
+        let local_angles: Vec<f32> = (0..self.bones.len()).map(|i| self.local_angle(i)).collect();
+        for i in &self.child_last_indices {
+            let index = *i;
+            self.bones[index].angle = pose.angles.get(index).map_or(local_angles[index], |angle| {
+                angle - self.bones[index].offset_angle
+            });
+            let values = self.calculate_bone(index);
+            self.bones[index].set(values);
         }
 
-        // TODO replace IK with angular constraints?
-        let (l1, l2, parent, index, origin, parent_rest_angle) = {
-            let bone = self.bone_by_name(name).unwrap();
-            (
-                self.bones[bone.parent].length(),
-                bone.length(),
-                bone.parent,
-                bone.index,
-                self.bones[bone.parent].start(),
-                // Parent angle offset after animation
-                self.bone_rest_angles[bone.parent].1 - self.bones[bone.parent].animation_angle
-            )
-        };
+    }
 
-        if let Some((a1, a2)) = solve_bone_ik(!positive, l1, l2, target.x - origin.x, target.y - origin.y) {
+    pub fn blend_pose(&mut self, pose: &Pose, weight: f32) {
+        let blended = self.capture_pose().lerp(pose, weight);
+        self.apply_pose(&blended);
+    }
 
-            self.bones[parent].angle = a1 + parent_rest_angle;
-            self.bones[index].angle = a2;
+    pub fn add_pose(&mut self, additive: &Pose, weight: f32) {
+        let combined = self.capture_pose().additive(additive, weight);
+        self.apply_pose(&combined);
+    }
 
-            let values = self.calculate_bone(parent);
-            self.bones[parent].set_ik(values);
 
-            let values = self.calculate_bone(index);
-            self.bones[index].set_ik(values);
+    // Look At ----------------------------------------------------------------
+    pub fn add_look_at(
+        &mut self,
+        tip: &str,
+        root: &str,
+        forward: f32,
+        max_turn_speed: f32
+
+    ) -> Result<LookAtId, SkeletonError> {
+
+        let tip = self.bone_id(tip).ok_or_else(|| SkeletonError::UnknownBone(tip.to_string()))?;
+        let root = self.bone_id(root).ok_or_else(|| SkeletonError::UnknownBone(root.to_string()))?;
+        let bones = self.bone_chain(tip.0, root.0)?;
+
+        // By default every bone takes an equal share of the total rotation
+        let count = bones.len();
+        let look_at = LookAt {
+            weights: (0..count).map(|i| 1.0 / (count - i) as f32).collect(),
+            limits: vec![(None, None); count],
+            offsets: vec![0.0; count],
+            bones,
+            forward,
+            max_turn_speed,
+            target: None
+        };
 
-        }
+        self.look_ats.push(look_at);
+        Ok(LookAtId(self.look_ats.len() - 1))
 
     }
 
-    /*
-    pub fn apply_bone_ik_new(&mut self, mut target: Vec2, bone: &str, root: &str, transformed: bool) {
+    pub fn set_look_at_bone(
+        &mut self,
+        id: LookAtId,
+        name: &str,
+        weight: f32,
+        min_angle: Option<f32>,
+        max_angle: Option<f32>
 
-        // Ignore setting IKs during ragdoll
-        if self.ragdoll.is_some() {
-            return;
+    ) -> Result<(), SkeletonError> {
 
-        // Transform IK target into animation space
-        } else if transformed {
-            target = target.scale(self.local_transform);
-        }
+        let index = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?.0;
+        let root = self.bones[self.look_ats[id.0].bones[0]].name().to_string();
+        let look_at = &mut self.look_ats[id.0];
+        let i = look_at.bones.iter().position(|b| *b == index).ok_or_else(|| {
+            SkeletonError::InvalidChain(name.to_string(), root)
+        })?;
 
-        let bone = self.bone_by_name(bone).unwrap().index();
-        let root = self.bone_by_name(root).unwrap().index();
+        look_at.weights[i] = weight.clamp(0.0, 1.0);
+        look_at.limits[i] = (min_angle, max_angle);
+        Ok(())
 
-        ccd_ik(target, bone, root, &mut self.bones[..], 3);
+    }
 
-    }*/
+    pub fn set_look_at_target(&mut self, id: LookAtId, target: Option<Vec2>) {
+        self.look_ats[id.0].target = target;
+    }
+
+    pub fn look_at_target(&self, id: LookAtId) -> Option<Vec2> {
+        self.look_ats[id.0].target
+    }
+
+
+    // IK Goals ---------------------------------------------------------------
+    pub fn add_ik_goal(
+        &mut self,
+        name: &str,
+        positive: bool,
+        priority: i32
+
+    ) -> Result<IKGoalId, SkeletonError> {
+
+        let index = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?.0;
+        let parent = self.bones[index].effective_parent.ok_or_else(|| {
+            SkeletonError::NoParent(name.to_string())
+        })?;
+
+        self.ik_goals.push(IKGoal {
+            bone: index,
+            parent,
+            positive,
+            priority,
+            weight: 1.0,
+            pole: None,
+            reach: IKReach::Keep,
+            error: 0.0,
+            bend: if positive { 1.0 } else { 0.0 },
+            swing_duration: 0.0,
+            blend_in: 0.0,
+            blend_out: 0.0,
+            blend: 0.0,
+            target: None,
+            active: false,
+            align: None,
+            lock_speed: 0.0,
+            lock_distance: 0.0,
+            lock_release: 0.0,
+            locked: None,
+            release: None,
+            last_position: None,
+            resolved: None
+        });
+
+        Ok(IKGoalId(self.ik_goals.len() - 1))
+
+    }
+
+    pub fn set_ik_goal_weight(&mut self, id: IKGoalId, weight: f32) {
+        self.ik_goals[id.0].weight = weight.clamp(0.0, 1.0);
+    }
+
+    pub fn set_ik_goal_blend(&mut self, id: IKGoalId, blend_in: f32, blend_out: f32) {
+        let goal = &mut self.ik_goals[id.0];
+        goal.blend_in = blend_in.max(0.0);
+        goal.blend_out = blend_out.max(0.0);
+    }
+
+    pub fn set_ik_goal_pole(&mut self, id: IKGoalId, pole: Option<(Space, Vec2)>, swing_duration: f32) {
+        let goal = &mut self.ik_goals[id.0];
+        goal.pole = pole;
+        goal.swing_duration = swing_duration.max(0.0);
+    }
+
+    pub fn set_ik_goal_reach(&mut self, id: IKGoalId, reach: IKReach) {
+        self.ik_goals[id.0].reach = reach;
+    }
+
+    // Bone which gets aligned with the surface below ground targets
+    pub fn set_ik_goal_align(&mut self, id: IKGoalId, name: Option<&str>) -> Result<(), SkeletonError> {
+        self.ik_goals[id.0].align = if let Some(name) = name {
+            Some(self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?.0)
+
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    // Ground targets which move slower than `max_speed` while in contact with
+    // the ground get pinned in world space until they either lift off or
+    // move further than `max_distance` away, then blend back over `release`
+    pub fn set_ik_goal_lock(&mut self, id: IKGoalId, max_speed: f32, max_distance: f32, release: f32) {
+        let goal = &mut self.ik_goals[id.0];
+        goal.lock_speed = max_speed.max(0.0);
+        goal.lock_distance = max_distance.max(0.0);
+        goal.lock_release = release.max(0.0);
+    }
+
+    pub fn ik_goal_locked(&self, id: IKGoalId) -> bool {
+        self.ik_goals[id.0].locked.is_some()
+    }
+
+    // Ground targets are probed up to `height` above and below the animated
+    // end point, lowering the root by up to `max_root_drop` for them
+    pub fn set_ground_probe(&mut self, height: f32, max_root_drop: f32) {
+        self.ground_probe_height = height.max(0.0);
+        self.ground_max_drop = max_root_drop.max(0.0);
+    }
+
+    pub fn set_ik_goal_target(&mut self, id: IKGoalId, target: Option<IKTarget>) {
+
+        // Goals without a target blend out towards their last one
+        let goal = &mut self.ik_goals[id.0];
+        goal.active = target.is_some();
+        if target.is_some() {
+            goal.target = target;
+        }
+
+    }
+
+    pub fn ik_goal_weight(&self, id: IKGoalId) -> f32 {
+        let goal = &self.ik_goals[id.0];
+        goal.weight * smoothstep(goal.blend)
+    }
+
+    // Distance between the bone's end and the target after the last step
+    pub fn ik_goal_error(&self, id: IKGoalId) -> f32 {
+        self.ik_goals[id.0].error
+    }
+
+
+    // Bones ------------------------------------------------------------------
+    pub fn bone_id(&self, name: &str) -> Option<BoneId> {
+        self.name_to_index.get(name).map(|index| BoneId(*index))
+    }
+
+    pub fn bone_name(&self, id: BoneId) -> &str {
+        self.bones[id.0].name()
+    }
+
+    pub fn effective_parent(&self, id: BoneId) -> Option<BoneId> {
+        self.bones[id.0].effective_parent.map(BoneId)
+    }
+
+    pub fn bone_start(&self, space: Space, name: &str) -> Result<Vec2, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        Ok(self.bone_start_by_id(space, id))
+    }
+
+    pub fn bone_start_by_id(&self, space: Space, id: BoneId) -> Vec2 {
+        let start = if let Some(ref ragdoll) = self.ragdoll {
+            self.ragdoll_constraints[id.0].map_or(Vec2::zero(), |c| {
+                ragdoll.constraint_points_by_index(c).1
+            })
+
+        } else {
+            self.animation_to_local(self.bones[id.0].start())
+        };
+        self.local_to_space(space, start)
+    }
+
+    pub fn bone_end(&self, space: Space, name: &str) -> Result<Vec2, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        Ok(self.bone_end_by_id(space, id))
+    }
+
+    pub fn bone_end_by_id(&self, space: Space, id: BoneId) -> Vec2 {
+        let end = if let Some(ref ragdoll) = self.ragdoll {
+            self.ragdoll_constraints[id.0].map_or(Vec2::zero(), |c| {
+                ragdoll.constraint_points_by_index(c).0
+            })
+
+        } else {
+            self.animation_to_local(self.bones[id.0].end())
+        };
+        self.local_to_space(space, end)
+    }
+
+    // Hit Testing ------------------------------------------------------------
+    pub fn hit_point(&self, space: Space, p: Vec2) -> Option<BoneHit> {
+
+        let p = self.space_to_local(space, p);
+        let mut best: Option<BoneHit> = None;
+        for (index, start, end, radius) in self.hitbox_capsules() {
+            let closest = closest_on_segment(start, end, p);
+            let distance = (p - closest).length();
+            if distance <= radius && best.is_none_or(|b| distance < b.distance) {
+                best = Some(BoneHit {
+                    bone: BoneId(index),
+                    point: closest,
+                    distance
+                });
+            }
+        }
+
+        best.map(|hit| self.hit_to_space(space, hit))
+
+    }
+
+    pub fn hit_segment(&self, space: Space, from: Vec2, to: Vec2) -> Option<BoneHit> {
+        let (from, to) = (self.space_to_local(space, from), self.space_to_local(space, to));
+        let delta = to - from;
+        let length = delta.length();
+        if length > f32::EPSILON {
+            self.hit_local_ray(from, delta / length, length).map(|hit| self.hit_to_space(space, hit))
+
+        } else {
+            self.hit_point(space, from)
+        }
+    }
+
+    pub fn hit_ray(&self, space: Space, origin: Vec2, direction: Vec2) -> Option<BoneHit> {
+        let origin = self.space_to_local(space, origin);
+        let direction = self.space_to_local(space, direction) - self.space_to_local(space, Vec2::zero());
+        if direction.length() > f32::EPSILON {
+            self.hit_local_ray(origin, direction.unit(), f32::INFINITY).map(|hit| self.hit_to_space(space, hit))
+
+        } else {
+            None
+        }
+    }
+
+    // Sockets ----------------------------------------------------------------
+    pub fn socket(&self, space: Space, name: &str) -> Option<(Vec2, f32)> {
+        let &(index, offset, angle) = self.sockets.get(name)?;
+        let (end, bone_angle) = self.bone_frame(index);
+        let position = self.animation_to_local(end + offset.rotate(bone_angle));
+        Some((self.local_to_space(space, position), self.angle_to_space(space, bone_angle + angle)))
+    }
+
+    // IK target which follows a socket or bone end during the next steps
+    pub fn attachment_target(&self, name: &str, offset: Vec2) -> Option<IKTarget> {
+        if let Some(&(index, local, _)) = self.sockets.get(name) {
+            Some(IKTarget::Attached(BoneId(index), local, offset))
+
+        } else {
+            self.bone_id(name).map(|id| IKTarget::Attached(id, Vec2::zero(), offset))
+        }
+    }
+
+    // Bone a socket or bone end is attached to
+    pub fn attachment_bone(&self, name: &str) -> Option<BoneId> {
+        if let Some(&(index, _, _)) = self.sockets.get(name) {
+            Some(BoneId(index))
+
+        } else {
+            self.bone_id(name)
+        }
+    }
+
+    pub fn attachment_point(&self, space: Space, name: &str) -> Result<Vec2, SkeletonError> {
+        if let Some((position, _)) = self.socket(space, name) {
+            Ok(position)
+
+        } else {
+            self.bone_end(space, name)
+        }
+    }
+
+    pub fn apply_bone_ik(
+        &mut self,
+        name: &str,
+        target: Vec2,
+        positive: bool,
+        transformed: bool
+
+    ) -> Result<Option<f32>, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        self.apply_bone_ik_by_id(id, target, positive, transformed)
+    }
+
+    pub fn apply_bone_ik_by_id(
+        &mut self,
+        id: BoneId,
+        target: Vec2,
+        positive: bool,
+        transformed: bool
+
+    ) -> Result<Option<f32>, SkeletonError> {
+        self.apply_bone_ik_with_by_id(id, target, IKOptions {
+            positive,
+            transformed,
+            ..IKOptions::default()
+        })
+    }
+
+    pub fn apply_bone_ik_with(&mut self, name: &str, target: Vec2, options: IKOptions) -> Result<Option<f32>, SkeletonError> {
+        let id = self.bone_id(name).ok_or_else(|| SkeletonError::UnknownBone(name.to_string()))?;
+        self.apply_bone_ik_with_by_id(id, target, options)
+    }
+
+    // Returns the remaining distance between the bone's end and the target,
+    // or None during ragdoll
+    pub fn apply_bone_ik_with_by_id(
+        &mut self,
+        id: BoneId,
+        mut target: Vec2,
+        options: IKOptions
+
+    ) -> Result<Option<f32>, SkeletonError> {
+
+        let index = id.0;
+        let parent = self.bones[index].effective_parent.ok_or_else(|| {
+            SkeletonError::NoParent(self.bones[index].name().to_string())
+        })?;
+
+        // Ignore setting IKs during ragdoll
+        if self.ragdoll.is_some() {
+            return Ok(None);
+        }
+
+        // Transform IK target into animation space
+        let mut pole = options.pole;
+        if options.transformed {
+            target = self.local_to_animation(target);
+            pole = pole.map(|p| self.local_to_animation(p));
+        }
+
+        let positive = pole.map_or(options.positive, |pole| {
+            self.pole_side(index, parent, target, pole, options.positive)
+        });
+
+        if let Some((a1, a2, stretch)) = self.solve_two_bone_ik(index, parent, target, positive, options.reach) {
+            self.bones[parent].stretch = stretch;
+            self.bones[index].stretch = stretch;
+            self.apply_world_angles(&[(parent, a1), (index, a1 + a2)]);
+        }
+
+        Ok(Some((self.bones[index].end() - target).length()))
+
+    }
+
+    pub fn apply_chain_ik(
+        &mut self,
+        tip: &str,
+        root: &str,
+        target: Vec2,
+        solver: IKSolver,
+        transformed: bool
+
+    ) -> Result<bool, SkeletonError> {
+        let tip = self.bone_id(tip).ok_or_else(|| SkeletonError::UnknownBone(tip.to_string()))?;
+        let root = self.bone_id(root).ok_or_else(|| SkeletonError::UnknownBone(root.to_string()))?;
+        self.apply_chain_ik_by_id(tip, root, target, solver, transformed)
+    }
+
+    pub fn apply_chain_ik_by_id(
+        &mut self,
+        tip: BoneId,
+        root: BoneId,
+        mut target: Vec2,
+        solver: IKSolver,
+        transformed: bool
+
+    ) -> Result<bool, SkeletonError> {
+
+        // Collect bones from root to tip
+        let chain = self.bone_chain(tip.0, root.0)?;
+
+        // Ignore setting IKs during ragdoll
+        if self.ragdoll.is_some() {
+            return Ok(false);
+        }
+
+        // Transform IK target into animation space
+        if transformed {
+            target = self.local_to_animation(target);
+        }
+
+        let lengths: Vec<f32> = chain.iter().map(|i| self.bones[*i].length()).collect();
+        let limits: Vec<(Option<f32>, Option<f32>)> = chain.iter().map(|i| {
+            (self.bones[*i].min_angle, self.bones[*i].max_angle)
+
+        }).collect();
+
+        // Angles relative to the parents include the offset angles, just
+        // like the limits do
+        let mut local_angles: Vec<f32> = chain.iter().map(|i| {
+            self.bones[*i].angle - self.parent_angle(*i)
+
+        }).collect();
+
+        let ccd = CCDChain {
+            origin: self.bones[chain[0]].start(),
+            base_angle: self.parent_angle(chain[0]),
+            lengths: &lengths[..],
+            limits: &limits[..]
+        };
+
+        let tolerance = match solver {
+            IKSolver::FABRIK { iterations, tolerance } => {
+
+                // Joint positions, starting at the root bone's start
+                let mut joints = Vec::with_capacity(chain.len() + 1);
+                joints.push(ccd.origin);
+                for i in &chain {
+                    joints.push(self.bones[*i].end());
+                }
+
+                solve_chain_ik(&mut joints[..], &lengths[..], target, iterations, tolerance);
+
+                // Zero length bones have no direction and keep their current angle
+                let mut parent_angle = ccd.base_angle;
+                let mut limited = false;
+                for (i, local) in local_angles.iter_mut().enumerate() {
+                    if lengths[i] > 0.0 {
+                        *local = (joints[i + 1] - joints[i]).angle() - parent_angle;
+                    }
+
+                    let (min, max) = limits[i];
+                    if min.is_some() || max.is_some() {
+                        let clamped = clamp_angle(*local, min, max);
+                        limited |= !f32_equals(clamped, local.sin().atan2(local.cos()));
+                        *local = clamped;
+                    }
+                    parent_angle += *local;
+                }
+
+                // FABRIK knows nothing about limits, so continue from the
+                // clamped solution with the limit aware solver instead
+                if limited {
+                    solve_chain_ik_ccd(&ccd, &mut local_angles[..], target, iterations, tolerance);
+                }
+
+                tolerance
+
+            },
+            IKSolver::CCD { iterations, tolerance } => {
+                solve_chain_ik_ccd(&ccd, &mut local_angles[..], target, iterations, tolerance);
+                tolerance
+            }
+        };
+
+        // Judge the final, limited, pose instead of the solver's guess
+        let mut joints = vec![ccd.origin; chain.len() + 1];
+        chain_joints(&ccd, &local_angles[..], &mut joints[..]);
+        let reached = (target - joints[chain.len()]).length() <= tolerance;
+
+        // Convert back into absolute angles
+        let mut angle = ccd.base_angle;
+        let angles: Vec<(usize, f32)> = chain.iter().zip(local_angles.iter()).map(|(index, local)| {
+            angle += *local;
+            (*index, angle)
+
+        }).collect();
+
+        self.apply_world_angles(&angles[..]);
+        Ok(reached)
+
+    }
 
     pub fn apply_bone_angle(&mut self, name: &str, angle: f32) {
-        if let Some(index) = self.name_to_index.get(name) {
-            self.bones[*index].set_angle(angle);
+        if let Some(id) = self.bone_id(name) {
+            self.apply_bone_angle_by_id(id, angle);
+        }
+    }
+
+    pub fn apply_bone_angle_by_id(&mut self, id: BoneId, angle: f32) {
+        self.bones[id.0].set_angle(angle);
+    }
+
+    pub fn visit<C: FnMut(Vec2, Vec2, &str)>(&mut self, mut callback: C, children_first: bool) {
+
+        if let Some(ref ragdoll) = self.ragdoll {
+            ragdoll.visit(callback);
+
+        } else {
+
+            let sequence = if children_first {
+                &self.child_first_indices
+
+            } else {
+                &self.child_last_indices
+            };
+
+            for i in sequence {
+                let bone = &self.bones[*i];
+                let start = self.animation_to_local(bone.start());
+                let end = self.animation_to_local(bone.end());
+                if !self.severed[*i] {
+                    callback(start, end, bone.name());
+
+                } else if let Some(at_length) = self.stumps[*i] {
+                    let stump = format!("{}.Stump", bone.name());
+                    callback(start, start + (end - start) * at_length, &stump);
+                }
+            }
+
+        }
+
+    }
+
+
+    // Debug ------------------------------------------------------------------
+    pub fn debug_draw<R: Renderer>(&self, renderer: &mut R, options: DebugDrawOptions) {
+
+        let offset = self.transform.position;
+        let size = DEBUG_MARKER_SIZE * self.transform.scale;
+
+        // Helper constraints of the ragdoll which are never visible otherwise
+        if let (true, Some(ragdoll)) = (options.constraints, self.ragdoll.as_ref()) {
+            ragdoll.visit_helpers(|start, end, typ| {
+                let color = if typ == ConstraintType::Angular {
+                    DEBUG_ANGULAR_COLOR
+
+                } else {
+                    DEBUG_STICK_COLOR
+                };
+                renderer.draw_line(start + offset, end + offset, color);
+            });
+        }
+
+        // Angle limits as arcs around the bone's start, relative to its parent
+        if options.limits && self.ragdoll.is_none() {
+            for bone in self.bones.iter().filter(|b| self.has_body(b.index)) {
+
+                let start = bone.start();
+                let parent_angle = self.parent_angle(bone.index);
+                let to_world = |angle: f32| {
+                    let p = start + Angle::offset(parent_angle + angle, DEBUG_LIMIT_RADIUS);
+                    self.to_world(self.animation_to_local(p))
+                };
+
+                let center = self.to_world(self.animation_to_local(start));
+                for limit in [bone.min_angle, bone.max_angle].iter().filter_map(|l| *l) {
+                    renderer.draw_line(center, to_world(limit), DEBUG_LIMIT_COLOR);
+                }
+
+                if let (Some(min), Some(max)) = (bone.min_angle, bone.max_angle) {
+                    let step = (max - min) / DEBUG_LIMIT_SEGMENTS as f32;
+                    for i in 0..DEBUG_LIMIT_SEGMENTS {
+                        let a = min + step * i as f32;
+                        renderer.draw_line(to_world(a), to_world(a + step), DEBUG_LIMIT_COLOR);
+                    }
+                }
+
+            }
+        }
+
+        // Joint markers and names
+        for bone in self.bones.iter().filter(|b| self.has_body(b.index)) {
+
+            let id = BoneId(bone.index);
+            let start = self.bone_start_by_id(Space::World, id);
+            let end = self.bone_end_by_id(Space::World, id);
+            if options.joints {
+                renderer.draw_circle(end, DEBUG_JOINT_RADIUS, DEBUG_JOINT_COLOR);
+            }
+
+            if options.names && bone.length > 0.0 {
+                renderer.draw_text(start + (end - start) * 0.5, bone.name(), DEBUG_NAME_COLOR);
+            }
+
+        }
+
+        // Resolved IK targets, pinned ones are highlighted
+        if options.ik_targets && self.ragdoll.is_none() {
+            for (i, goal) in self.ik_goals.iter().enumerate() {
+                if let Some(target) = goal.resolved {
+
+                    if self.ik_goal_weight(IKGoalId(i)) <= 0.0 || self.severed[goal.bone] {
+                        continue;
+                    }
+
+                    let p = self.to_world(self.animation_to_local(target));
+                    let color = if goal.locked.is_some() {
+                        DEBUG_LOCKED_COLOR
+
+                    } else {
+                        DEBUG_TARGET_COLOR
+                    };
+                    renderer.draw_line(p - Vec2::new(size, size), p + Vec2::new(size, size), color);
+                    renderer.draw_line(p - Vec2::new(size, -size), p + Vec2::new(size, -size), color);
+
+                }
+            }
+        }
+
+        // Sockets with their direction
+        if options.sockets {
+            for (name, &(index, _, _)) in &self.sockets {
+                if !self.has_body(index) {
+                    continue;
+                }
+                if let Some((p, angle)) = self.socket(Space::World, name) {
+                    renderer.draw_circle(p, DEBUG_JOINT_RADIUS, DEBUG_SOCKET_COLOR);
+                    renderer.draw_line(p, p + Angle::offset(angle, size * 2.0), DEBUG_SOCKET_COLOR);
+                }
+            }
+        }
+
+        if options.bounds {
+            let (min, max) = self.world_bounds();
+            let color = if self.at_rest() {
+                DEBUG_REST_COLOR
+
+            } else {
+                DEBUG_ACTIVE_COLOR
+            };
+            renderer.draw_rect(min, max, color);
+        }
+
+    }
+
+
+    // Internal ---------------------------------------------------------------
+    fn local_to_space(&self, space: Space, p: Vec2) -> Vec2 {
+        match space {
+            Space::World => self.to_world(p),
+            Space::Local => p,
+            Space::Animation => self.local_to_animation(p)
+        }
+    }
+
+    fn space_to_animation(&self, space: Space, p: Vec2) -> Vec2 {
+        match space {
+            Space::World => self.local_to_animation(self.to_local(p)),
+            Space::Local => self.local_to_animation(p),
+            Space::Animation => p
+        }
+    }
+
+    fn space_to_local(&self, space: Space, p: Vec2) -> Vec2 {
+        match space {
+            Space::World => self.to_local(p),
+            Space::Local => p,
+            Space::Animation => self.animation_to_local(p)
+        }
+    }
+
+    // Whether a bone is part of the animated skeleton or its ragdoll
+    fn has_body(&self, index: usize) -> bool {
+        !self.severed[index] && (self.ragdoll.is_none() || self.ragdoll_constraints[index].is_some())
+    }
+
+    // Local space segment of the stump a bone left behind when it was cut
+    fn stump_segment(&self, index: usize) -> Option<(Vec2, Vec2)> {
+        let bone = &self.bones[index];
+        if let Some(ref ragdoll) = self.ragdoll {
+            // Stumps of severed parents are no longer part of the ragdoll
+            let ci = ragdoll.constraint_index(&format!("{}.Stump", bone.name()))?;
+            let (cut, start) = ragdoll.constraint_points_by_index(ci);
+            Some((start, cut))
+
+        } else {
+            let at_length = self.stumps[index]?;
+            if bone.ragdoll_parent.is_some_and(|p| self.severed[p]) {
+                None
+
+            } else {
+                let start = self.animation_to_local(bone.start());
+                let end = self.animation_to_local(bone.end());
+                Some((start, start + (end - start) * at_length))
+            }
+        }
+    }
+
+    // Hitbox capsules of all bones and stumps in local space, following the
+    // ragdoll
+    fn hitbox_capsules(&self) -> Vec<(usize, Vec2, Vec2, f32)> {
+        let scale = self.transform.scale;
+        self.bones.iter().filter(|bone| bone.radius > 0.0).filter_map(|bone| {
+            let id = BoneId(bone.index);
+            let segment = if self.has_body(bone.index) {
+                Some((
+                    self.bone_start_by_id(Space::Local, id),
+                    self.bone_end_by_id(Space::Local, id)
+                ))
+
+            } else if self.severed[bone.index] {
+                self.stump_segment(bone.index)
+
+            } else {
+                None
+            };
+            segment.map(|(start, end)| (bone.index, start, end, bone.radius * scale))
+
+        }).collect()
+    }
+
+    fn hit_local_ray(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> Option<BoneHit> {
+        let mut best: Option<BoneHit> = None;
+        for (index, start, end, radius) in self.hitbox_capsules() {
+            if let Some(t) = ray_capsule(origin, direction, start, end, radius) {
+                if t <= max_distance && best.is_none_or(|b| t < b.distance) {
+                    best = Some(BoneHit {
+                        bone: BoneId(index),
+                        point: origin + direction * t,
+                        distance: t
+                    });
+                }
+            }
+        }
+        best
+    }
+
+    fn hit_to_space(&self, space: Space, hit: BoneHit) -> BoneHit {
+        BoneHit {
+            bone: hit.bone,
+            point: self.local_to_space(space, hit.point),
+            distance: if space == Space::Animation {
+                hit.distance / self.transform.scale
+
+            } else {
+                hit.distance
+            }
+        }
+    }
+
+    fn angle_to_space(&self, space: Space, angle: f32) -> f32 {
+        match space {
+            Space::World | Space::Local => self.animation_to_local(Angle::offset(angle, 1.0)).angle(),
+            Space::Animation => angle
+        }
+    }
+
+    // End point and angle of a bone in animation space
+    fn bone_frame(&self, index: usize) -> (Vec2, f32) {
+
+        if let Some(ref ragdoll) = self.ragdoll {
+            if let Some(c) = self.ragdoll_constraints[index] {
+                let (end, start) = ragdoll.constraint_points_by_index(c);
+                let dir = self.local_to_animation(end - start);
+                let angle = if dir.length() > f32::EPSILON {
+                    dir.angle()
+
+                } else {
+                    self.bones[index].angle
+                };
+                return (self.local_to_animation(end), angle);
+            }
+        }
+
+        (self.bones[index].end(), self.bones[index].angle)
+
+    }
+
+    fn to_ragdoll(&self) -> Result<Ragdoll, SkeletonError> {
+
+        let particles = self.bones.iter().map(|bone| {
+            Particle::with_inv_mass(self.animation_to_local(bone.end()), bone.inv_mass)
+
+        }).collect();
+
+        let scale = self.transform.scale;
+        let mut constraints: Vec<Box<dyn Constraint>> = self.bones.iter().filter_map(|bone| {
+            bone.to_constaint(scale)
+
+        }).collect();
+
+        // Additional skeletal constraints
+        for constraint in &self.data.constraints {
+            match *constraint {
+                SkeletalConstraint::Stick(ref parent, ref child) => {
+                    let parent = self.constraint_bone_index(parent)?;
+                    let child = self.constraint_bone_index(child)?;
+                    let ap = self.bones[parent].end();
+                    let bp = self.bones[child].end();
+                    constraints.push(
+                        Box::new(StickConstraint::new(
+                            format!("s-{}-{}", parent, child),
+                            parent,
+                            child,
+                            (ap - bp).length() * scale
+                        ))
+                    );
+                },
+                SkeletalConstraint::Angular(ref parent, ref joint, ref child, left, right) => {
+                    let parent = self.constraint_bone_index(parent)?;
+                    let joint = self.constraint_bone_index(joint)?;
+                    let child = self.constraint_bone_index(child)?;
+                    constraints.append(&mut self.angular_constraints(parent, joint, child, left, right));
+                }
+            }
+        }
+
+        Ok(Ragdoll::new(particles, constraints))
+
+    }
+
+    fn to_partial_ragdoll(&self) -> Result<PartialRagdoll, SkeletonError> {
+        let mut ragdoll = self.to_ragdoll()?;
+        let sockets = self.split_ragdoll(&mut ragdoll)?;
+        Ok(PartialRagdoll {
+            ragdoll,
+            sockets,
+            local_transform: self.local_transform,
+            scale: self.transform.scale,
+            world_position: self.transform.position,
+            ends: Vec::with_capacity(self.bones.len()),
+            joints: Vec::with_capacity(self.bones.len()),
+            angles: Vec::with_capacity(self.bones.len())
+        })
+    }
+
+    // Splits the configured joints from their parents, returning the new
+    // socket joints along with the joints they were split from
+    fn split_ragdoll(&self, ragdoll: &mut Ragdoll) -> Result<Vec<(usize, usize)>, SkeletonError> {
+
+        let mut sockets = Vec::with_capacity(self.data.ragdoll_splits.len());
+        for &(ref name, limits) in &self.data.ragdoll_splits {
+
+            let index = *self.name_to_index.get(name).ok_or_else(|| {
+                SkeletonError::UnknownRagdollBone(name.clone())
+            })?;
+
+            let socket = ragdoll.split_bone_from_parent(self.bones[index].name());
+            if let (Some(socket), Some(parent)) = (socket, self.bones[index].ragdoll_parent) {
+                sockets.push((socket, parent));
+            }
+
+            // Limit the children of the split bone around its new socket
+            if let (Some(socket), Some((left, right))) = (socket, limits) {
+                for child in self.bones.iter().filter(|b| b.ragdoll_parent == Some(index)) {
+                    for c in self.angular_constraints(socket, index, child.index, left, right) {
+                        ragdoll.add_constraint(c);
+                    }
+                }
+            }
+
+        }
+
+        Ok(sockets)
+
+    }
+
+    fn step_partial_ragdoll<C: Fn(&mut Particle)>(&mut self, dt: f32, gravity: Vec2, collider: C) {
+
+        // Drop the simulation once all bones are fully animated again
+        if self.ragdoll_weights.iter().all(|w| *w <= 0.0) {
+            self.partial_ragdoll = None;
+            return;
+        }
+
+        // (Re-)start the simulation from the animated pose, particles are
+        // stored in local space and cannot be kept when the skeleton flips
+        // or gets scaled, rotations are simulated just like movement
+        let (local_transform, scale) = (self.local_transform, self.transform.scale);
+        let partial = match self.partial_ragdoll.take() {
+            Some(ref p) if p.local_transform != local_transform || p.scale != scale => None,
+            partial => partial
+        }.map_or_else(|| self.to_partial_ragdoll(), Ok);
+
+        // Without a valid simulation the bones simply stay animated
+        let mut partial = match partial {
+            Ok(partial) => partial,
+            Err(_) => return
+        };
+
+        // Keep simulated joints in place when the skeleton moves through the world
+        partial.ragdoll.translate(partial.world_position - self.transform.position);
+        partial.world_position = self.transform.position;
+
+        partial.ends.clear();
+        partial.ends.extend(self.bones.iter().map(|bone| bone.end()));
+
+        // Fully animated joints are pinned to their animated positions
+        for bone in &self.bones {
+            let pinned = self.animation_to_local(partial.ends[bone.index]);
+            let joint = partial.ragdoll.joint_mut(bone.index);
+            if self.ragdoll_weights[bone.index] > 0.0 {
+                joint.set_invmass(bone.inv_mass);
+
+            } else {
+                joint.set_invmass(0.0);
+                joint.set_position(pinned);
+            }
         }
+
+        // Split bones stay attached to the joint they were split from
+        for &(socket, parent) in &partial.sockets {
+            let position = partial.ragdoll.joint_position(parent);
+            let joint = partial.ragdoll.joint_mut(socket);
+            joint.set_invmass(0.0);
+            joint.set_position(position);
+        }
+
+        partial.ragdoll.activate();
+        partial.ragdoll.step(dt, gravity, collider);
+
+        partial.joints.clear();
+        for i in 0..self.bones.len() {
+            partial.joints.push(self.local_to_animation(partial.ragdoll.joint_position(i)));
+        }
+
+        // Rotate weighted bones towards their blended end points while
+        // keeping their lengths and limits intact
+        partial.angles.clear();
+        partial.angles.extend((0..self.bones.len()).map(|i| self.local_angle(i)));
+        for i in &self.child_last_indices {
+
+            let index = *i;
+            let weight = self.ragdoll_weights[index];
+
+            self.bones[index].angle = if weight > 0.0 && self.bones[index].length > 0.0 {
+                let start = self.bones[index].parent.map_or(self.root_offset + self.ground_offset, |p| self.bones[p].end());
+                let end = partial.ends[index] * (1.0 - weight) + partial.joints[index] * weight;
+                (end - start).angle() - self.parent_angle(index) - self.bones[index].offset_angle
+
+            } else {
+                partial.angles[index]
+            };
+
+            let values = self.calculate_bone(index);
+            self.bones[index].set(values);
+            Skeleton::extend_bounds(&mut self.bounds, &self.bones[index]);
+
+        }
+
+        self.partial_ragdoll = Some(partial);
+
     }
 
-    pub fn visit<C: FnMut(Vec2, Vec2, &str)>(&mut self, mut callback: C, children_first: bool) {
+    fn step_look_ats(&mut self, dt: f32) {
+
+        for l in 0..self.look_ats.len() {
+
+            // Targets are given in world space
+            let target = self.look_ats[l].target.map(|t| {
+                self.local_to_animation(self.to_local(t))
+            });
+
+            let tip = *self.look_ats[l].bones.last().unwrap();
+            let forward = self.look_ats[l].forward;
+            let max_turn = self.look_ats[l].max_turn_speed * dt;
+
+            // Each bone takes its weighted share of the remaining rotation,
+            // so that later bones only correct what earlier ones left over
+            for i in 0..self.look_ats[l].bones.len() {
+
+                let index = self.look_ats[l].bones[i];
+                let desired = target.map_or(0.0, |target| {
+                    let bone = &self.bones[tip];
+                    let r = (target - bone.start()).angle() - (bone.angle + forward);
+                    let (min_angle, max_angle) = self.look_ats[l].limits[i];
+                    clamp_angle(
+                        r.sin().atan2(r.cos()) * self.look_ats[l].weights[i],
+                        min_angle,
+                        max_angle
+                    )
+                });
+
+                // Limit the turn speed
+                let current = self.look_ats[l].offsets[i];
+                let offset = current + (desired - current).min(max_turn).max(-max_turn);
+                self.look_ats[l].offsets[i] = offset;
+
+                if offset != 0.0 {
+                    let angle = self.bones[index].angle + offset;
+                    self.apply_world_angles(&[(index, angle)]);
+                }
 
-        if let Some(ref ragdoll) = self.ragdoll {
-            ragdoll.visit(callback);
+            }
+
+        }
+
+    }
+
+    fn step_ik_goals<C: Fn(&mut Particle)>(&mut self, dt: f32, collider: &C) {
+
+        // Update blend factors
+        for goal in &mut self.ik_goals {
+            let duration = if goal.active { goal.blend_in } else { goal.blend_out };
+            let delta = if duration > 0.0 { dt / duration } else { 1.0 };
+            goal.blend = if goal.active {
+                (goal.blend + delta).min(1.0)
+
+            } else {
+                (goal.blend - delta).max(0.0)
+            };
+        }
+
+        // Resolve targets from the animated pose, ground targets also
+        // provide the surface normal and how close the bone is to it
+        let mut targets: Vec<Option<GoalTarget>> = (0..self.ik_goals.len()).map(|i| {
+            if self.severed[self.ik_goals[i].bone] {
+                return None;
+            }
+            match self.ik_goals[i].target {
+                Some(IKTarget::Point(space, p)) => Some(GoalTarget {
+                    position: self.space_to_animation(space, p),
+                    normal: None,
+                    contact: 0.0
+                }),
+                Some(IKTarget::Ground) => Some(self.ground_target(self.ik_goals[i].bone, collider)),
+                Some(IKTarget::Attached(..)) | None => None
+            }
+
+        }).collect();
+
+        self.lock_ik_goals(dt, &mut targets[..]);
+        self.lower_root(&targets[..]);
+
+        // Attached targets follow the pose after the root was lowered
+        for (goal, target) in self.ik_goals.iter().zip(targets.iter_mut()) {
+            if let Some(IKTarget::Attached(id, local, offset)) = goal.target {
+                if !self.severed[goal.bone] {
+                    let (end, angle) = self.bone_frame(id.0);
+                    *target = Some(GoalTarget {
+                        position: end + local.rotate(angle) + offset,
+                        normal: None,
+                        contact: 0.0
+                    });
+                }
+            }
+        }
+
+        for (goal, target) in self.ik_goals.iter_mut().zip(&targets) {
+            goal.resolved = target.map(|t| t.position);
+        }
+
+        // Higher priority goals are resolved last and win over earlier ones
+        let mut order: Vec<usize> = (0..self.ik_goals.len()).collect();
+        order.sort_by_key(|i| self.ik_goals[*i].priority);
+
+        for i in order {
+
+            let (index, parent, positive, pole, reach, align) = {
+                let goal = &self.ik_goals[i];
+                (goal.bone, goal.parent, goal.positive, goal.pole, goal.reach, goal.align)
+            };
+
+            let weight = self.ik_goal_weight(IKGoalId(i));
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let (target, normal, contact) = if let Some(ref target) = targets[i] {
+                (target.position, target.normal, target.contact)
+
+            } else {
+                continue;
+            };
+
+            // Swing the bend direction over towards the pole's side
+            let side = pole.map_or(positive, |(space, p)| {
+                let pole = self.space_to_animation(space, p);
+                self.pole_side(index, parent, target, pole, positive)
+            });
+
+            let bend = {
+                let goal = &mut self.ik_goals[i];
+                let delta = if goal.swing_duration > 0.0 { dt / goal.swing_duration } else { 1.0 };
+                goal.bend = if side {
+                    (goal.bend + delta).min(1.0)
+
+                } else {
+                    (goal.bend - delta).max(0.0)
+                };
+                goal.bend
+            };
+
+            let solution = if bend >= 1.0 || bend <= 0.0 {
+                self.solve_two_bone_ik(index, parent, target, bend >= 1.0, reach)
+
+            } else if let (Some(p), Some(n)) = (
+                self.solve_two_bone_ik(index, parent, target, true, reach),
+                self.solve_two_bone_ik(index, parent, target, false, reach)
+            ) {
+                // Mid swing the limb passes through its stretched out pose
+                let t = smoothstep(bend);
+                let a1 = Angle::lerp(n.0, p.0, t);
+                Some((a1, Angle::lerp(n.0 + n.1, p.0 + p.1, t) - a1, n.2 + (p.2 - n.2) * t))
+
+            } else {
+                None
+            };
+
+            // Blend from the current pose towards the solution
+            if let Some((a1, a2, stretch)) = solution {
+                let parent_angle = Angle::lerp(self.bones[parent].angle, a1, weight);
+                let angle = Angle::lerp(self.bones[index].angle, a1 + a2, weight);
+                let stretch = 1.0 + (stretch - 1.0) * weight;
+                self.bones[parent].stretch = stretch;
+                self.bones[index].stretch = stretch;
+                self.apply_world_angles(&[(parent, parent_angle), (index, angle)]);
+            }
+
+            self.ik_goals[i].error = (self.bones[index].end() - target).length();
+
+            // Align with the ground's surface while in contact with it
+            if let (Some(align), Some(normal)) = (align, normal) {
+                let tangent = Vec2::new(-normal.y, normal.x);
+                let angle = Angle::lerp(self.bones[align].angle, tangent.angle(), weight * contact);
+                self.apply_world_angles(&[(align, angle)]);
+            }
+
+        }
+
+    }
+
+    // Animation space target for a bone's end point on the ground, the
+    // surface normal is only known when the ground is probed
+    fn ground_target<C: Fn(&mut Particle)>(&self, index: usize, collider: &C) -> GoalTarget {
+
+        let end = self.animation_to_local(self.bones[index].end());
+        let height = self.ground_probe_height;
+
+        let mut target = end;
+        let mut normal = None;
+        let mut contact = 0.0;
+        if height > 0.0 {
+
+            // Follow the ground's height relative to the one below the root
+            let root = self.animation_to_local(self.root_offset);
+            if let (Some(reference), Some(ground)) = (
+                probe_ground(collider, Vec2::new(root.x, end.y), height),
+                probe_ground(collider, end, height)
+            ) {
+
+                target.y += ground.y - reference.y;
+
+                let left = probe_ground(collider, end - Vec2::new(GROUND_NORMAL_OFFSET, 0.0), height);
+                let right = probe_ground(collider, end + Vec2::new(GROUND_NORMAL_OFFSET, 0.0), height);
+                let surface = if let (Some(left), Some(right)) = (left, right) {
+                    let tangent = (right - left).unit();
+                    Vec2::new(tangent.y, -tangent.x)
+
+                } else {
+                    Vec2::new(0.0, -1.0)
+                };
+
+                let lift = (ground.y - target.y).max(0.0);
+                contact = 1.0 - (lift / GROUND_CONTACT_HEIGHT).min(1.0);
+                normal = Some(self.local_to_animation(surface).unit());
+
+            }
+
+        }
+
+        // Never sink into the ground
+        let mut p = Particle::new(target);
+        collider(&mut p);
+        if (p.position - target).length() > f32::EPSILON {
+            contact = 1.0;
+        }
+
+        GoalTarget {
+            position: self.local_to_animation(p.position),
+            normal,
+            contact
+        }
+
+    }
+
+    // Pin planted ground targets in world space until they lift off again
+    fn lock_ik_goals(&mut self, dt: f32, targets: &mut [Option<GoalTarget>]) {
+
+        for (i, target) in targets.iter_mut().enumerate().take(self.ik_goals.len()) {
+
+            if self.ik_goals[i].lock_speed <= 0.0 {
+                continue;
+            }
+
+            let ground = self.ik_goals[i].active && self.ik_goals[i].target == Some(IKTarget::Ground);
+            let target = match *target {
+                Some(ref mut target) if ground => target,
+                _ => {
+                    let goal = &mut self.ik_goals[i];
+                    goal.locked = None;
+                    goal.release = None;
+                    goal.last_position = None;
+                    continue;
+                }
+            };
+
+            let world = self.to_world(self.animation_to_local(target.position));
+            let pinned = {
+
+                let goal = &mut self.ik_goals[i];
+
+                // Planted targets barely move while in contact with the ground
+                let speed = match goal.last_position {
+                    Some(last) if dt > 0.0 => (world - last).length() / dt,
+                    _ => goal.lock_speed
+                };
+                goal.last_position = Some(world);
+
+                let grounded = target.contact >= 0.5;
+                if let Some(lock) = goal.locked {
+                    if !grounded || (world - lock).length() > goal.lock_distance {
+                        goal.locked = None;
+                        goal.release = Some((lock, 0.0));
+                    }
+
+                // Wait for releases to finish so that targets never pop
+                } else if grounded && speed < goal.lock_speed && goal.release.is_none() {
+                    goal.locked = Some(world);
+                }
+
+                // Blend from the released lock back towards the animated target
+                if let Some(lock) = goal.locked {
+                    Some(lock)
+
+                } else if let Some((from, timer)) = goal.release {
+                    let timer = timer + dt;
+                    let t = if goal.lock_release > 0.0 {
+                        smoothstep((timer / goal.lock_release).min(1.0))
+
+                    } else {
+                        1.0
+                    };
+                    goal.release = if t < 1.0 { Some((from, timer)) } else { None };
+                    Some(from + (world - from) * t)
+
+                } else {
+                    None
+                }
+
+            };
+
+            if let Some(p) = pinned {
+                target.position = self.space_to_animation(Space::World, p);
+            }
+
+        }
+
+    }
+
+    fn lower_root(&mut self, targets: &[Option<GoalTarget>]) {
+
+        if self.ground_max_drop <= 0.0 {
+            return;
+        }
+
+        // Drop along the world's down direction until every ground target
+        // is within reach of its bones
+        let down = self.local_to_animation(Vec2::new(0.0, 1.0)).unit();
+        let mut drop: f32 = 0.0;
+        for (i, goal) in self.ik_goals.iter().enumerate() {
+
+            let target = match (goal.target, targets[i].as_ref()) {
+                (Some(IKTarget::Ground), Some(target)) => target.position,
+                _ => continue
+            };
+
+            let max_stretch = match goal.reach {
+                IKReach::Stretch(ratio) => ratio.max(1.0),
+                _ => 1.0
+            };
+
+            let length = (self.bones[goal.parent].length() + self.bones[goal.bone].length()) * max_stretch;
+            let v = target - self.bones[goal.parent].start();
+            let distance = v.length();
+            if distance <= length {
+                continue;
+            }
+
+            let along = v.dot(down);
+            let d = along * along - distance * distance + length * length;
+            let needed = if d >= 0.0 {
+                along - d.sqrt()
+
+            } else {
+                along
+            };
+
+            drop = drop.max(needed * self.ik_goal_weight(IKGoalId(i)));
+
+        }
+
+        let drop = drop.min(self.ground_max_drop);
+        if drop > 0.0 {
+            let offset = down * drop;
+            self.ground_offset = offset;
+            for bone in &mut self.bones {
+                bone.start = bone.start + offset;
+                bone.end = bone.end + offset;
+            }
+        }
+
+    }
+
+    fn step_recovery(&mut self, dt: f32) {
+
+        let mut recovery = if let Some(recovery) = self.recovery.take() {
+            recovery
 
         } else {
+            return;
+        };
 
-            let sequence = if children_first {
-                &self.child_first_indices
+        recovery.timer += dt;
+
+        let t = smoothstep((recovery.timer / recovery.duration).min(1.0));
+
+        // Move the body from the captured torso position towards the animation
+        let offset = recovery.torso.map_or(Vec2::zero(), |(index, start)| {
+            (start - self.bones[index].start()) * (1.0 - t)
+        });
+
+        // Rotate captured bones from their ragdoll directions towards the animation
+        let animated_angles: Vec<f32> = self.bones.iter().map(|bone| bone.angle).collect();
+        let local_angles: Vec<f32> = (0..self.bones.len()).map(|i| self.local_angle(i)).collect();
+        for i in &self.child_last_indices {
+
+            let index = *i;
+            self.bones[index].angle = if let Some(from) = recovery.angles[index] {
+                let angle = Angle::lerp(from, animated_angles[index], t);
+                angle - self.parent_angle(index) - self.bones[index].offset_angle
 
             } else {
-                &self.child_last_indices
+                local_angles[index]
             };
 
-            for i in sequence {
-                let bone = &self.bones[*i];
-                let start = bone.start().scale(self.local_transform);
-                let end = bone.end().scale(self.local_transform);
-                callback(start, end, bone.name());
+            // Children follow the offset root
+            let (angle, start, end) = self.calculate_bone(index);
+            if self.bones[index].parent.is_none() {
+                self.bones[index].set((angle, start + offset, end + offset));
+
+            } else {
+                self.bones[index].set((angle, start, end));
             }
+            Skeleton::extend_bounds(&mut self.bounds, &self.bones[index]);
+
+        }
 
+        if recovery.timer < recovery.duration {
+            self.recovery = Some(recovery);
         }
 
     }
 
+    fn record_joints(&mut self, dt: f32) {
 
-    // Internal ---------------------------------------------------------------
-    fn bone_by_name(&self, name: &str) -> Option<&Bone> {
-        if let Some(index) = self.name_to_index.get(name) {
-            Some(&self.bones[*index])
+        if dt <= 0.0 {
+            return;
+        }
+
+        let joints = self.bones.iter().map(|bone| {
+            self.to_world(self.animation_to_local(bone.end()))
+
+        }).collect();
+
+        if self.joint_history.len() >= VELOCITY_HISTORY_FRAMES {
+            self.joint_history.pop_front();
+        }
+        self.joint_history.push_back((dt, joints));
+
+    }
+
+    // Verlet particles move by the difference to their previous position on
+    // every step, so the velocity is turned back into the last step's offset
+    fn seed_ragdoll_velocities(&self, ragdoll: &mut Ragdoll) {
+        if let Some(&(dt, _)) = self.joint_history.back() {
+            for bone in &self.bones {
+                let velocity = self.bone_velocity_by_id(BoneId(bone.index));
+                let joint = ragdoll.joint_mut(bone.index);
+                joint.prev_position = joint.position - velocity * dt;
+            }
+        }
+    }
+
+    fn extend_bounds(bounds: &mut (Vec2, Vec2), bone: &Bone) {
+        bounds.0.x = bounds.0.x.min(bone.start.x).min(bone.end.x);
+        bounds.0.y = bounds.0.y.min(bone.start.y).min(bone.end.y);
+        bounds.1.x = bounds.1.x.max(bone.start.x).max(bone.end.x);
+        bounds.1.y = bounds.1.y.max(bone.start.y).max(bone.end.y);
+    }
+
+    fn angular_constraints(
+        &self,
+        parent: usize,
+        joint: usize,
+        child: usize,
+        left: f32,
+        right: f32
+
+    ) -> Vec<Box<dyn Constraint>> {
+
+        // Mirrored skeletons swap sides
+        let (left, right) = if self.local_transform.x * self.local_transform.y < 0.0 {
+            (right, left)
 
         } else {
-            None
+            (left, right)
+        };
+
+        let a = self.bones[child].length() * self.transform.scale;
+        let b = self.bones[joint].length() * self.transform.scale;
+
+        [(left, true), (right, false)].iter().map(|&(angle, is_left)| {
+            let rest_length = (a * a + b * b - 2.0 * a * b * angle.cos()).sqrt();
+            Box::new(AngularConstraint::new(
+                format!("a-{}-{}-{}", parent, joint, child),
+                parent,
+                child,
+                joint,
+                rest_length,
+                is_left
+
+            )) as Box<dyn Constraint>
+
+        }).collect()
+
+    }
+
+    fn constraint_bone_index(&self, name: &str) -> Result<usize, SkeletonError> {
+        self.name_to_index.get(name).cloned().ok_or_else(|| {
+            SkeletonError::UnknownConstraintBone(name.to_string())
+        })
+    }
+
+    fn bone_chain(&self, tip: usize, root: usize) -> Result<Vec<usize>, SkeletonError> {
+
+        // Walk up from the tip until we reach the root bone
+        let mut chain = vec![tip];
+        let mut current = tip;
+        while current != root {
+            current = self.bones[current].parent.ok_or_else(|| {
+                SkeletonError::InvalidChain(
+                    self.bones[tip].name().to_string(),
+                    self.bones[root].name().to_string()
+                )
+            })?;
+            chain.push(current);
+        }
+
+        chain.reverse();
+        Ok(chain)
+
+    }
+
+    // Absolute angles of a parent and its child bone reaching for a target,
+    // along with the stretch factor applied to both of their lengths
+    fn solve_two_bone_ik(
+        &self,
+        index: usize,
+        parent: usize,
+        target: Vec2,
+        positive: bool,
+        reach: IKReach
+
+    ) -> Option<(f32, f32, f32)> {
+
+        // TODO replace IK with angular constraints?
+        let (l1, l2, origin, base_angle) = (
+            self.bones[parent].length(),
+            self.bones[index].length(),
+            self.bones[parent].start(),
+            // Angle of the parent's parent after animation
+            self.parent_angle(parent)
+        );
+
+        // Rotation introduced by zero length bones between the two
+        let child_offset = self.parent_angle(index) - self.bones[parent].angle;
+
+        let (stretch, delta) = reach_bone_ik(reach, l1, l2, target - origin);
+
+        let (parent_bone, bone) = (&self.bones[parent], &self.bones[index]);
+        let pair = BonePair {
+            l1: l1 * stretch,
+            l2: l2 * stretch,
+            base_angle,
+            parent_limits: (parent_bone.min_angle, parent_bone.max_angle),
+            child_limits: (bone.min_angle, bone.max_angle),
+            child_offset
+        };
+
+        solve_bone_ik_limited(&pair, !positive, delta).map(|(a1, a2)| (a1, a2, stretch))
+
+    }
+
+    // Bend direction which places the joint between both bones closest to the pole
+    fn pole_side(&self, index: usize, parent: usize, target: Vec2, pole: Vec2, positive: bool) -> bool {
+
+        let (l1, l2, origin) = (
+            self.bones[parent].length(),
+            self.bones[index].length(),
+            self.bones[parent].start()
+        );
+
+        let delta = target - origin;
+        match (
+            solve_bone_ik(false, l1, l2, delta.x, delta.y),
+            solve_bone_ik(true, l1, l2, delta.x, delta.y)
+        ) {
+            (Some((p, _)), Some((n, _))) => {
+                let p = (origin + Angle::offset(p, l1) - pole).length();
+                let n = (origin + Angle::offset(n, l1) - pole).length();
+                if f32_equals(p, n) {
+                    positive
+
+                } else {
+                    p < n
+                }
+            },
+            // Fully stretched limbs have no bend direction
+            _ => positive
+        }
+
+    }
+
+    fn parent_angle(&self, index: usize) -> f32 {
+        self.bones[index].parent.map_or(0.0, |parent| self.bones[parent].angle)
+    }
+
+    fn local_angle(&self, index: usize) -> f32 {
+        let bone = &self.bones[index];
+        bone.angle - self.parent_angle(index) - bone.offset_angle
+    }
+
+    fn apply_world_angles(&mut self, angles: &[(usize, f32)]) {
+
+        // Descendants keep their angles relative to their parents
+        let local_angles: Vec<f32> = (0..self.bones.len()).map(|i| self.local_angle(i)).collect();
+        let mut dirty = vec![false; self.bones.len()];
+
+        for i in &self.child_last_indices {
+
+            let index = *i;
+            let parent = self.bones[index].parent;
+            let target = angles.iter().find(|a| a.0 == index).map(|a| a.1);
+
+            dirty[index] = target.is_some() || parent.is_some_and(|p| dirty[p]);
+            if !dirty[index] {
+                continue;
+            }
+
+            let parent_angle = self.parent_angle(index);
+
+            self.bones[index].angle = if let Some(angle) = target {
+                angle - parent_angle - self.bones[index].offset_angle
+
+            } else {
+                local_angles[index]
+            };
+
+            let values = self.calculate_bone(index);
+            self.bones[index].set(values);
+
         }
+
     }
 
     fn visit_bones<C: FnMut(&Bone)>(
@@ -614,30 +2654,24 @@ impl Skeleton {
             let bone = &self.bones[index];
 
             // Get bone's parent's angle
-            let parent_angle = if bone.parent == 255 {
-                0.0
-
-            } else {
-                self.bones[bone.parent].angle
-            };
+            let parent_angle = self.parent_angle(index);
 
-            parent_angle + bone.angle + bone.offset_angle
+            parent_angle + clamp_angle(
+                bone.angle + bone.offset_angle,
+                bone.min_angle,
+                bone.max_angle
+            )
 
         };
 
         let bone = &self.bones[index];
 
         // Get starting offset from bone's parent
-        let start = if bone.parent == 255 {
-            Vec2::zero()
-
-        } else {
-            self.bones[bone.parent].end()
-        };
+        let start = bone.parent.map_or(self.root_offset + self.ground_offset, |parent| self.bones[parent].end());
 
         // Calculate end offset from angle and length
         let end = if bone.length() > 0.0 {
-            start + Angle::offset(bone_angle, bone.length())
+            start + Angle::offset(bone_angle, bone.length() * bone.stretch)
 
         } else {
             start
@@ -645,8 +2679,89 @@ impl Skeleton {
 
         (bone_angle, start, end)
 
-    }
+    }
+
+}
+
+
+// Partial Ragdoll State ------------------------------------------------------
+struct PartialRagdoll {
+    ragdoll: Ragdoll,
+    // Split socket joints and the joints they follow
+    sockets: Vec<(usize, usize)>,
+    local_transform: Vec2,
+    scale: f32,
+    world_position: Vec2,
+    // Scratch buffers for the animated ends, simulated ends and local
+    // angles of all bones
+    ends: Vec<Vec2>,
+    joints: Vec<Vec2>,
+    angles: Vec<f32>
+}
+
+
+// Look At State --------------------------------------------------------------
+struct LookAt {
+    // Bones from root to tip
+    bones: Vec<usize>,
+    weights: Vec<f32>,
+    limits: Vec<(Option<f32>, Option<f32>)>,
+    offsets: Vec<f32>,
+    // Angle of the look direction relative to the tip bone
+    forward: f32,
+    max_turn_speed: f32,
+    target: Option<Vec2>
+}
+
+
+// IK Goal State --------------------------------------------------------------
+struct IKGoal {
+    bone: usize,
+    // Effective parent which bends together with the bone
+    parent: usize,
+    positive: bool,
+    priority: i32,
+    weight: f32,
+    pole: Option<(Space, Vec2)>,
+    reach: IKReach,
+    error: f32,
+    // Bend direction from negative (0) to positive (1)
+    bend: f32,
+    swing_duration: f32,
+    blend_in: f32,
+    blend_out: f32,
+    blend: f32,
+    target: Option<IKTarget>,
+    active: bool,
+    align: Option<usize>,
+    lock_speed: f32,
+    lock_distance: f32,
+    lock_release: f32,
+    // World space positions of the pinned and released targets
+    locked: Option<Vec2>,
+    release: Option<(Vec2, f32)>,
+    last_position: Option<Vec2>,
+    // Animation space target of the last step
+    resolved: Option<Vec2>
+}
+
+#[derive(Copy, Clone)]
+struct GoalTarget {
+    // Animation space position
+    position: Vec2,
+    normal: Option<Vec2>,
+    contact: f32
+}
+
 
+// Ragdoll Recovery State -----------------------------------------------------
+struct RagdollRecovery {
+    angles: Vec<Option<f32>>,
+    // Torso bone and the start of it in the ragdoll, in animation space
+    // relative to the world offset
+    torso: Option<(usize, Vec2)>,
+    duration: f32,
+    timer: f32
 }
 
 
@@ -654,35 +2769,38 @@ impl Skeleton {
 #[derive(Debug)]
 pub struct Bone {
     index: usize,
-    parent: usize,
-    ragdoll_parent: usize,
+    parent: Option<usize>,
+    effective_parent: Option<usize>,
+    ragdoll_parent: Option<usize>,
     children: Vec<usize>,
 
     angle: f32,
-    animation_angle: f32,
     offset_angle: f32,
 
     start: Vec2,
     end: Vec2,
 
+    name: String,
+    length: f32,
+    // Temporary length factor from IK, reset on every step
+    stretch: f32,
+    radius: f32,
+    inv_mass: f32,
+    // Limits of the bone's angle relative to its parent, including the offset
+    // angle. Positive angles rotate clockwise in animation space, where the
+    // skeleton always faces right.
     min_angle: Option<f32>,
-    max_angle: Option<f32>,
-
-    data: &'static SkeletalBone
+    max_angle: Option<f32>
 }
 
 impl Bone {
 
     pub fn name(&self) -> &str {
-        self.data.0
-    }
-
-    pub fn index(&self) -> usize {
-        self.index
+        &self.name
     }
 
     pub fn length(&self) -> f32 {
-        (self.data.1).1
+        self.length
     }
 
     pub fn set_angle(&mut self, r: f32) {
@@ -690,34 +2808,20 @@ impl Bone {
     }
 
     // Internal ---------------------------------------------------------------
-    fn to_constaint(&self) -> Option<Box<Constraint>> {
-        if self.ragdoll_parent != 255 {
+    fn to_constaint(&self, scale: f32) -> Option<Box<dyn Constraint>> {
+        self.ragdoll_parent.map(|parent| {
             let mut c = StickConstraint::new(
                 self.name().to_string(),
                 self.index,
-                self.ragdoll_parent,
-                self.length()
+                parent,
+                self.length() * scale
             );
             c.set_visual(true);
-            Some(Box::new(c))
-
-        } else {
-            None
-        }
-    }
-
-    fn to_particle(&self, transform: Vec2) -> Particle {
-        Particle::with_inv_mass(self.end().scale(transform), (self.data.1).3)
+            Box::new(c) as Box<dyn Constraint>
+        })
     }
 
     fn set(&mut self, values: (f32, Vec2, Vec2)) {
-        self.angle = values.0;
-        self.animation_angle = values.0;
-        self.start = values.1;
-        self.end = values.2;
-    }
-
-    fn set_ik(&mut self, values: (f32, Vec2, Vec2)) {
         self.angle = values.0;
         self.start = values.1;
         self.end = values.2;
@@ -731,19 +2835,6 @@ impl Bone {
         self.end
     }
 
-    /*
-    fn set_start_ik(&mut self, p: Vec2) {
-        self.start = p;
-        self.end = self.end_computed();
-    }
-
-    fn end_computed(&self) -> Vec2 {
-        Vec2::new(
-            self.start.x + self.angle.cos() * self.length(),
-            self.start.y + self.angle.sin() * self.length()
-        )
-    }*/
-
 }
 
 
@@ -814,109 +2905,960 @@ fn solve_bone_ik(solve_positive: bool, l1: f32, l2: f32, x: f32, y: f32) -> Opti
 
 }
 
-/*
-fn ccd_ik(target: Vec2, child: usize, root: usize, bones: &mut[Bone], iterations: usize) {
+// A parent bone and its child for two bone IK
+struct BonePair {
+    l1: f32,
+    l2: f32,
+    // Absolute angle of the parent bone's parent
+    base_angle: f32,
+    parent_limits: (Option<f32>, Option<f32>),
+    child_limits: (Option<f32>, Option<f32>),
+    // Rotation introduced by zero length bones between the two
+    child_offset: f32
+}
 
-    // Drag child to target
-    {
-        let bone = &mut bones[child];
-        let delta = target - bone.start();
-        bone.angle = delta.y.atan2(delta.x);
-        let start = target - Vec2::new(
-            bone.angle.cos() * bone.length(),
-            bone.angle.sin() * bone.length()
-        );
-        bone.set_start_ik(start);
+fn solve_bone_ik_limited(pair: &BonePair, prefer_positive: bool, target: Vec2) -> Option<(f32, f32)> {
+
+    let &BonePair { l1, l2, base_angle, parent_limits, child_limits, child_offset } = pair;
+    let mut best: Option<(f32, f32, f32)> = None;
+    for positive in &[prefer_positive, !prefer_positive] {
+
+        let (a1, a2) = solve_bone_ik(*positive, l1, l2, target.x, target.y)?;
+
+        // Clamp the child and re-aim the parent towards the target, the
+        // child's limits are relative to any skipped zero length bones
+        let clamped_a2 = child_offset + clamp_angle(a2 - child_offset, child_limits.0, child_limits.1);
+        let a1 = if f32_equals(clamped_a2, a2) {
+            a1
+
+        } else {
+            target.angle() - (l2 * clamped_a2.sin()).atan2(l1 + l2 * clamped_a2.cos())
+        };
+
+        let clamped_a1 = base_angle + clamp_angle(a1 - base_angle, parent_limits.0, parent_limits.1);
+
+        // Pick the solution which ends up closest to the target
+        let end = Angle::offset(clamped_a1, l1) + Angle::offset(clamped_a1 + clamped_a2, l2);
+        let error = (end - target).length();
+        if error < IK_LIMIT_TOLERANCE {
+            return Some((clamped_a1, clamped_a2));
+
+        } else if best.is_none_or(|b| error < b.2) {
+            best = Some((clamped_a1, clamped_a2, error));
+        }
+
+    }
+
+    best.map(|b| (b.0, b.1))
+
+}
+
+fn closest_on_segment(a: Vec2, b: Vec2, p: Vec2) -> Vec2 {
+    let ab = b - a;
+    let length = ab.dot(ab);
+    if length > f32::EPSILON {
+        a + ab * ((p - a).dot(ab) / length).clamp(0.0, 1.0)
+
+    } else {
+        a
+    }
+}
+
+// Distance along a ray with a unit direction to its first intersection with
+// the capsule around a and b
+fn ray_capsule(origin: Vec2, direction: Vec2, a: Vec2, b: Vec2, radius: f32) -> Option<f32> {
+
+    // Rays starting inside of the capsule hit immediately
+    if (origin - closest_on_segment(a, b, origin)).length() <= radius {
+        return Some(0.0);
     }
 
-    let mut bone_list = Vec::new();
-    for iter in 0..iterations {
+    let mut best: Option<f32> = None;
+    let mut hit = |t: f32| {
+        if t >= 0.0 && best.is_none_or(|b| t < b) {
+            best = Some(t);
+        }
+    };
 
-        // Inverse phase
-        let mut current = child;
-        loop {
+    // Round caps
+    for center in &[a, b] {
+        let m = origin - *center;
+        let (along, offset) = (m.dot(direction), m.dot(m) - radius * radius);
+        let d = along * along - offset;
+        if d >= 0.0 {
+            hit(-along - d.sqrt());
+        }
+    }
 
-            // Stop in case there are no further parents
-            let parent = bones[current].parent;
-            if parent == 255 {
-                break;
+    // Straight sides
+    let ab = b - a;
+    let length = ab.length();
+    if length > f32::EPSILON {
+        let axis = ab / length;
+        let normal = Vec2::new(-axis.y, axis.x);
+        let denom = direction.dot(normal);
+        if denom.abs() > f32::EPSILON {
+            for side in &[radius, -radius] {
+                let t = (a + normal * *side - origin).dot(normal) / denom;
+                let u = (origin + direction * t - a).dot(axis);
+                if u >= 0.0 && u <= length {
+                    hit(t);
+                }
             }
+        }
+    }
+
+    best
+
+}
+
+// First point at which the collider pushes a particle out, bisecting the
+// span from `height` above to `height` below `p`
+fn probe_ground<C: Fn(&mut Particle)>(collider: &C, p: Vec2, height: f32) -> Option<Vec2> {
+
+    let sample = |y: f32| {
+        let sample = Vec2::new(p.x, y);
+        let mut particle = Particle::new(sample);
+        collider(&mut particle);
+        if (particle.position - sample).length() > f32::EPSILON {
+            Some(particle.position)
+
+        } else {
+            None
+        }
+    };
+
+    let (mut top, mut bottom) = (p.y - height, p.y + height);
+    if let Some(ground) = sample(top) {
+        return Some(ground);
+    }
+
+    let mut ground = sample(bottom)?;
+    while bottom - top > GROUND_PROBE_STEP {
+        let middle = (top + bottom) * 0.5;
+        if let Some(pushed) = sample(middle) {
+            ground = pushed;
+            bottom = middle;
 
-            // Only build bone list during first iteration
-            if iter == 0 {
-                bone_list.push(bones[current].index());
+        } else {
+            top = middle;
+        }
+    }
+
+    Some(ground)
+
+}
+
+fn reach_bone_ik(reach: IKReach, l1: f32, l2: f32, delta: Vec2) -> (f32, Vec2) {
+
+    let max_stretch = match reach {
+        IKReach::Keep => return (1.0, delta),
+        IKReach::Extend => 1.0,
+        IKReach::Stretch(ratio) => ratio.max(1.0)
+    };
+
+    let (length, distance) = (l1 + l2, delta.length());
+    if length <= f32::EPSILON || distance <= f32::EPSILON {
+        return (1.0, delta);
+    }
+
+    // Stretch towards targets out of reach and pull the remaining distance
+    // into the solvable range so that the limb extends towards it
+    let stretch = (distance / length).max(1.0).min(max_stretch);
+    let min = (l1 - l2).abs() * stretch * (1.0 + IK_REACH_TOLERANCE);
+    let max = length * stretch * (1.0 - IK_REACH_TOLERANCE);
+    (stretch, delta * (distance.max(min).min(max) / distance))
+
+}
+
+fn solve_chain_ik(joints: &mut [Vec2], lengths: &[f32], target: Vec2, iterations: usize, tolerance: f32) -> bool {
+
+    let tip = lengths.len();
+    let origin = joints[0];
+    let total_length: f32 = lengths.iter().sum();
+
+    // If the target is out of reach, fully extend the chain towards it
+    let delta = target - origin;
+    if delta.length() >= total_length {
+        let dir = if delta.length() > f32::EPSILON {
+            delta.unit()
+
+        } else {
+            Vec2::new(1.0, 0.0)
+        };
+        for i in 0..tip {
+            joints[i + 1] = joints[i] + dir * lengths[i];
+        }
+        return false;
+    }
+
+    for _ in 0..iterations {
+
+        if (joints[tip] - target).length() <= tolerance {
+            return true;
+        }
+
+        // Backward phase, drag the tip onto the target
+        joints[tip] = target;
+        for i in (0..tip).rev() {
+            joints[i] = place_joint(joints[i + 1], joints[i], lengths[i]);
+        }
+
+        // Forward phase, move the root back onto its origin
+        joints[0] = origin;
+        for i in 0..tip {
+            joints[i + 1] = place_joint(joints[i], joints[i + 1], lengths[i]);
+        }
+
+    }
+
+    (joints[tip] - target).length() <= tolerance
+
+}
+
+fn place_joint(anchor: Vec2, joint: Vec2, length: f32) -> Vec2 {
+    let delta = joint - anchor;
+    let distance = delta.length();
+    if distance > f32::EPSILON {
+        anchor + delta * (length / distance)
+
+    } else {
+        anchor + Vec2::new(length, 0.0)
+    }
+}
+
+// A bone chain for CCD, with angles relative to each bone's parent
+struct CCDChain<'a> {
+    origin: Vec2,
+    // Absolute angle of the root bone's parent
+    base_angle: f32,
+    lengths: &'a [f32],
+    limits: &'a [(Option<f32>, Option<f32>)]
+}
+
+fn solve_chain_ik_ccd(
+    chain: &CCDChain,
+    angles: &mut [f32],
+    target: Vec2,
+    iterations: usize,
+    tolerance: f32
+
+) -> bool {
+
+    let tip = chain.lengths.len();
+    let mut joints = vec![chain.origin; tip + 1];
+
+    for _ in 0..iterations {
+
+        // Rotate each bone, starting at the tip, so that the end effector
+        // points towards the target
+        for i in (0..tip).rev() {
+
+            chain_joints(chain, angles, &mut joints[..]);
+
+            let to_end = joints[tip] - joints[i];
+            let to_target = target - joints[i];
+            if (target - joints[tip]).length() <= tolerance {
+                return true;
+
+            } else if to_end.length() > f32::EPSILON && to_target.length() > f32::EPSILON {
+                angles[i] = clamp_angle(
+                    angles[i] + to_end.angle_between(to_target),
+                    chain.limits[i].0,
+                    chain.limits[i].1
+                );
             }
 
-            // Point parent at child start
-            let child_start = bones[current].start();
-            let delta = child_start - bones[parent].start();
-            let angle = delta.y.atan2(delta.x);
-            bones[parent].angle = angle;
+        }
+
+    }
+
+    chain_joints(chain, angles, &mut joints[..]);
+    (target - joints[tip]).length() <= tolerance
+
+}
+
+fn chain_joints(chain: &CCDChain, angles: &[f32], joints: &mut [Vec2]) {
+    let mut angle = chain.base_angle;
+    joints[0] = chain.origin;
+    for (i, length) in chain.lengths.iter().enumerate() {
+        angle += angles[i];
+        joints[i + 1] = joints[i] + Angle::offset(angle, *length);
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn clamp_angle(mut angle: f32, min: Option<f32>, max: Option<f32>) -> f32 {
+
+    if min.is_none() && max.is_none() {
+        return angle;
+    }
+
+    // Normalize into the range of -PI to PI
+    angle = angle.sin().atan2(angle.cos());
 
-            // Stop after root child, we don't want to peposition it
-            if parent == root {
-                break;
+    if let Some(min) = min {
+        angle = angle.max(min);
+    }
+
+    if let Some(max) = max {
+        angle = angle.min(max);
+    }
+
+    angle
+
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod test {
+
+    use std::cell::Cell;
+    use super::*;
+
+    fn chain_skeleton() -> Skeleton {
+        Skeleton::new(SkeletalData::from_ron(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("Root", 10.0, 0.0, 1.0, Some(-0.6), Some(0.6))),
+            ("B", ("A", 10.0, 0.0, 1.0, None, None)),
+            ("C", ("B", 10.0, 0.0, 1.0, None, None)),
+        ])"#).unwrap())
+    }
+
+    fn solve_chain(solver: IKSolver, offset: f32, target: Vec2) -> (bool, Vec2) {
+        let mut skeleton = chain_skeleton();
+        skeleton.apply_bone_angle("A", offset);
+        skeleton.apply_bone_angle("B", offset);
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        let reached = skeleton.apply_chain_ik("C", "A", target, solver, false).unwrap();
+        (reached, skeleton.bone_end_by_id(Space::Animation, skeleton.bone_id("C").unwrap()))
+    }
+
+    fn validate(ron: &str) -> Result<(), SkeletonError> {
+        SkeletalData::from_ron(ron).unwrap().validate()
+    }
+
+    #[test]
+    fn test_validate_roots() {
+        assert!(chain_skeleton().data.validate().is_ok());
+        match validate("(bones: [])") {
+            Err(SkeletonError::NoRoot) => {},
+            r => panic!("expected no root, got {:?}", r)
+        }
+        match validate(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("Other", ("Other", 0.0, 0.0, 1.0, None, None)),
+        ])"#) {
+            Err(SkeletonError::MultipleRoots(a, b)) => assert_eq!((a.as_str(), b.as_str()), ("Root", "Other")),
+            r => panic!("expected multiple roots, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn test_validate_bones() {
+        match validate(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("Root", 1.0, 0.0, 1.0, None, None)),
+            ("A", ("Root", 1.0, 0.0, 1.0, None, None)),
+        ])"#) {
+            Err(SkeletonError::DuplicateBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected a duplicate bone, got {:?}", r)
+        }
+        match validate(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("B", 1.0, 0.0, 1.0, None, None)),
+        ])"#) {
+            Err(SkeletonError::MissingParent(bone, parent)) => assert_eq!((bone.as_str(), parent.as_str()), ("A", "B")),
+            r => panic!("expected a missing parent, got {:?}", r)
+        }
+        match validate(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("B", 1.0, 0.0, 1.0, None, None)),
+            ("B", ("A", 1.0, 0.0, 1.0, None, None)),
+        ])"#) {
+            Err(SkeletonError::Cycle(name)) => assert_eq!(name, "A"),
+            r => panic!("expected a cycle, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn test_validate_references() {
+        let bones = r#"bones: [("Root", ("Root", 0.0, 0.0, 1.0, None, None))]"#;
+        match validate(&format!(r#"({}, constraints: [Stick("Root", "A")])"#, bones)) {
+            Err(SkeletonError::UnknownConstraintBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected an unknown constraint bone, got {:?}", r)
+        }
+        match validate(&format!(r#"({}, ragdoll_parents: [("A", "Root")])"#, bones)) {
+            Err(SkeletonError::UnknownRagdollBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected an unknown ragdoll bone, got {:?}", r)
+        }
+        match validate(&format!(r#"({}, ragdoll_splits: [("A", None)])"#, bones)) {
+            Err(SkeletonError::UnknownRagdollBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected an unknown ragdoll bone, got {:?}", r)
+        }
+        match validate(&format!(r#"({}, sockets: [("Hat", ("A", 0.0, 0.0, 0.0))])"#, bones)) {
+            Err(SkeletonError::UnknownSocketBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected an unknown socket bone, got {:?}", r)
+        }
+        match validate(&format!(r#"({}, sockets: [("Hat", ("Root", 0.0, 0.0, 0.0)), ("Hat", ("Root", 1.0, 0.0, 0.0))])"#, bones)) {
+            Err(SkeletonError::DuplicateSocket(name)) => assert_eq!(name, "Hat"),
+            r => panic!("expected a duplicate socket, got {:?}", r)
+        }
+        match validate(&format!(r#"({}, hitboxes: [("A", 1.0)])"#, bones)) {
+            Err(SkeletonError::UnknownHitboxBone(name)) => assert_eq!(name, "A"),
+            r => panic!("expected an unknown hitbox bone, got {:?}", r)
+        }
+    }
+
+    #[test]
+    fn test_unknown_bone_positions() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        assert_eq!(skeleton.bone_end(Space::Local, "A").unwrap(), Vec2::new(10.0, 0.0));
+        for result in &[
+            skeleton.bone_start(Space::Local, "Foo"),
+            skeleton.bone_end(Space::Local, "Foo"),
+            skeleton.bone_velocity("Foo"),
+            skeleton.attachment_point(Space::Local, "Foo")
+        ] {
+            match *result {
+                Err(SkeletonError::UnknownBone(ref name)) => assert_eq!(name, "Foo"),
+                ref r => panic!("expected an unknown bone error, got {:?}", r)
             }
+        }
+    }
 
-            // Reposition parent
-            let length = bones[parent].length();
-            bones[parent].set_start_ik(child_start - Vec2::new(
-                angle.cos() * length,
-                angle.sin() * length
-            ));
+    #[test]
+    fn test_partial_ragdoll_errors() {
+        // Invalid splits are rejected before any ragdoll is built
+        let data = SkeletalData::from_ron(r#"(
+            bones: [
+                ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+                ("A", ("Root", 10.0, 0.0, 1.0, None, None)),
+            ],
+            ragdoll_splits: [("B", None)]
+        )"#).unwrap();
+        match Skeleton::try_new(data) {
+            Err(SkeletonError::UnknownRagdollBone(name)) => assert_eq!(name, "B"),
+            r => panic!("expected an unknown ragdoll bone, got {:?}", r.err())
+        }
 
-            // Repeat with next parent
-            current = parent;
+        let mut skeleton = chain_skeleton();
+        match skeleton.set_ragdoll_weight("Foo", 1.0) {
+            Err(SkeletonError::UnknownBone(name)) => assert_eq!(name, "Foo"),
+            r => panic!("expected an unknown bone, got {:?}", r)
+        }
+    }
 
+    #[test]
+    fn test_new_rejects_missing_parents() {
+        let data = SkeletalData::from_ron(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("Foo", 10.0, 0.0, 1.0, None, None)),
+        ])"#).unwrap();
+        match Skeleton::try_new(data.clone()) {
+            Err(SkeletonError::MissingParent(bone, parent)) => assert_eq!((bone.as_str(), parent.as_str()), ("A", "Foo")),
+            r => panic!("expected a missing parent, got {:?}", r.err())
         }
+        assert!(::std::panic::catch_unwind(|| Skeleton::new(data)).is_err());
+    }
 
-        // Angle phase
-        for i in bone_list.iter() {
+    #[test]
+    fn test_partial_ragdoll_keeps_split_bones_attached() {
+        let mut skeleton = Skeleton::new(SkeletalData::from_ron(r#"(
+            bones: [
+                ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+                ("A", ("Root", 10.0, 0.0, 1.0, None, None)),
+                ("B", ("A", 10.0, 0.0, 1.0, None, None)),
+                ("C", ("B", 10.0, 0.0, 1.0, None, None)),
+            ],
+            ragdoll_splits: [("B", None)]
+        )"#).unwrap());
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        skeleton.set_ragdoll_weight("B", 1.0).unwrap();
+        skeleton.set_ragdoll_weight("C", 1.0).unwrap();
+        for _ in 0..60 {
+            skeleton.step(0.016, Vec2::new(0.0, 500.0), |_| {});
+        }
 
-            let parent_position = bones[bones[*i].parent].start();
-            let bone = &mut bones[*i];
+        // The split socket follows the animated bone instead of falling off
+        let partial = skeleton.partial_ragdoll.as_ref().unwrap();
+        let (socket, parent) = partial.sockets[0];
+        assert_eq!(parent, 1);
+        assert!((partial.ragdoll.joint_position(socket) - Vec2::new(10.0, 0.0)).length() < 0.01);
+    }
 
-            // Axis of parent and child
-            let parent_axis = parent_position - bone.start();
-            let child_end = bone.end_computed();
-            let child_axis = bone.start() - child_end;
+    #[test]
+    fn test_pose_lasts_until_the_next_step() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        let animated = skeleton.capture_pose();
+        let end = skeleton.bone_end(Space::Animation, "C").unwrap();
+
+        let mut pose = animated.clone();
+        pose.offset = Vec2::new(5.0, 2.0);
+        skeleton.apply_pose(&pose);
+        assert_eq!(skeleton.bone_end(Space::Animation, "C").unwrap(), end + Vec2::new(5.0, 2.0));
+
+        // Replicating the same pose every frame must not drift the root
+        for _ in 0..3 {
+            skeleton.step(0.016, Vec2::zero(), |_| {});
+            assert_eq!(skeleton.bone_end(Space::Animation, "C").unwrap(), end);
+            assert_eq!(skeleton.capture_pose().offset, Vec2::zero());
+            skeleton.apply_pose(&pose);
+            assert_eq!(skeleton.bone_end(Space::Animation, "C").unwrap(), end + Vec2::new(5.0, 2.0));
+        }
+    }
 
-            // Relative angle between parent and child
-            let parent_angle = parent_axis.angle();
-            let rel_angle = child_axis.angle_between(parent_axis);
+    #[test]
+    fn test_recovery_blends_out_root_offset() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        skeleton.start_ragdoll().unwrap();
+        for _ in 0..60 {
+            skeleton.step(0.016, Vec2::new(0.0, 500.0), |p| {
+                p.position.y = p.position.y.min(20.0);
+            });
+        }
 
-            // Clamp angles
-            if let Some(min_angle) = bone.min_angle {
-                if rel_angle < min_angle {
-                    bone.angle = parent_angle + (PI - min_angle);
-                    let start = child_end - Vec2::new(
-                        bone.angle.cos() * bone.length(),
-                        bone.angle.sin() * bone.length()
-                    );
-                    bone.set_start_ik(start);
-                }
-            }
+        // The body starts out where the ragdoll came to rest...
+        skeleton.recover_from_ragdoll(0.5).unwrap();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        let start = skeleton.bone_start(Space::Local, "A").unwrap();
+        assert!(start.y > 19.0);
 
-            if let Some(max_angle) = bone.max_angle {
-                if rel_angle > max_angle {
-                    bone.angle = parent_angle + (PI - max_angle);
-                    let start = child_end - Vec2::new(
-                        bone.angle.cos() * bone.length(),
-                        bone.angle.sin() * bone.length()
-                    );
-                    bone.set_start_ik(start);
-               }
+        // ...and ends up at the animated position
+        for _ in 0..40 {
+            skeleton.step(0.016, Vec2::zero(), |_| {});
+        }
+        let start = skeleton.bone_start(Space::Local, "A").unwrap();
+        assert!(start.length() < 0.01);
+    }
+
+    #[test]
+    fn test_attached_ik_target_follows_current_pose() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+
+        let goal = skeleton.add_ik_goal("C", true, 0).unwrap();
+        let target = skeleton.attachment_target("A", Vec2::new(5.0, 8.0));
+        skeleton.set_ik_goal_target(goal, target);
+
+        // The target is resolved against the pose of the same step
+        skeleton.apply_bone_angle("A", 0.5);
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        let a = skeleton.bone_end(Space::Animation, "A").unwrap();
+        let c = skeleton.bone_end(Space::Animation, "C").unwrap();
+        assert!((a - Vec2::new(0.5f32.cos(), 0.5f32.sin()) * 10.0).length() < 0.001);
+        assert!((c - (a + Vec2::new(5.0, 8.0))).length() < 0.01);
+        assert_eq!(skeleton.attachment_target("Foo", Vec2::zero()), None);
+    }
+
+    #[test]
+    fn test_bone_ik_pole() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+
+        let target = Vec2::new(18.0, 0.0);
+        let down = IKOptions { pole: Some(Vec2::new(8.0, 20.0)), ..IKOptions::default() };
+        assert!(skeleton.apply_bone_ik_with("B", target, down).unwrap().unwrap() < 0.001);
+        assert!(skeleton.bone_end(Space::Animation, "A").unwrap().y > 0.0);
+
+        let up = IKOptions { pole: Some(Vec2::new(8.0, -20.0)), ..IKOptions::default() };
+        assert!(skeleton.apply_bone_ik_with("B", target, up).unwrap().unwrap() < 0.001);
+        assert!(skeleton.bone_end(Space::Animation, "A").unwrap().y < 0.0);
+
+        // Without a pole the bend direction is picked by the flag
+        let a = {
+            skeleton.apply_bone_ik("B", target, false, false).unwrap();
+            skeleton.bone_end(Space::Animation, "A").unwrap()
+        };
+        let b = {
+            skeleton.apply_bone_ik("B", target, true, false).unwrap();
+            skeleton.bone_end(Space::Animation, "A").unwrap()
+        };
+        assert!(a.y * b.y < 0.0);
+    }
+
+    #[test]
+    fn test_reach_keep() {
+        let delta = Vec2::new(30.0, 0.0);
+        assert_eq!(reach_bone_ik(IKReach::Keep, 10.0, 10.0, delta), (1.0, delta));
+    }
+
+    #[test]
+    fn test_reach_extend() {
+        // Targets within reach are left alone...
+        let delta = Vec2::new(12.0, 5.0);
+        assert_eq!(reach_bone_ik(IKReach::Extend, 10.0, 10.0, delta), (1.0, delta));
+
+        // ...while others get pulled just into the solvable range
+        let (stretch, delta) = reach_bone_ik(IKReach::Extend, 10.0, 10.0, Vec2::new(0.0, 30.0));
+        assert_eq!(stretch, 1.0);
+        assert!(delta.x.abs() < 0.0001);
+        assert!(delta.y < 20.0 && delta.y > 19.9);
+        assert!(solve_bone_ik(true, 10.0, 10.0, delta.x, delta.y).is_some());
+
+        // Targets too close to the parent get pushed out
+        let (_, delta) = reach_bone_ik(IKReach::Extend, 10.0, 4.0, Vec2::new(1.0, 0.0));
+        assert!(delta.x > 6.0 && delta.x < 6.1);
+    }
+
+    #[test]
+    fn test_reach_stretch() {
+        // Stretching covers the distance up to the given ratio...
+        let (stretch, delta) = reach_bone_ik(IKReach::Stretch(1.2), 10.0, 10.0, Vec2::new(22.0, 0.0));
+        assert!((stretch - 1.1).abs() < 0.0001);
+        assert!(delta.x < 22.0 && delta.x > 21.9);
+
+        // ...and extends beyond it
+        let (stretch, delta) = reach_bone_ik(IKReach::Stretch(1.2), 10.0, 10.0, Vec2::new(30.0, 0.0));
+        assert!((stretch - 1.2).abs() < 0.0001);
+        assert!(delta.x < 24.0 && delta.x > 23.9);
+
+        // Ratios below one never shrink the bones
+        let (stretch, _) = reach_bone_ik(IKReach::Stretch(0.5), 10.0, 10.0, Vec2::new(30.0, 0.0));
+        assert_eq!(stretch, 1.0);
+    }
+
+    #[test]
+    fn test_bone_ik_out_of_reach() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+
+        // The remaining distance is reported instead of silently doing nothing
+        let options = IKOptions { reach: IKReach::Extend, ..IKOptions::default() };
+        let distance = skeleton.apply_bone_ik_with("B", Vec2::new(30.0, 0.0), options).unwrap().unwrap();
+        assert!((distance - 10.0).abs() < 0.1);
+        assert!((skeleton.bone_end(Space::Animation, "B").unwrap() - Vec2::new(20.0, 0.0)).length() < 0.1);
+    }
+
+    #[test]
+    fn test_probe_ground() {
+        let calls = Cell::new(0);
+        let slope = |p: &mut Particle| {
+            calls.set(calls.get() + 1);
+            let floor = 10.0 + p.position.x * 0.5;
+            p.position.y = p.position.y.min(floor);
+        };
+
+        let ground = probe_ground(&slope, Vec2::new(4.0, 5.0), 20.0).unwrap();
+        assert_eq!(ground, Vec2::new(4.0, 12.0));
+        assert!(calls.get() <= 8);
+
+        // Nothing to hit within the span
+        assert_eq!(probe_ground(&slope, Vec2::new(4.0, -30.0), 20.0), None);
+
+        // Starting inside the ground
+        assert_eq!(probe_ground(&slope, Vec2::new(4.0, 40.0), 20.0), Some(Vec2::new(4.0, 12.0)));
+    }
+
+    fn assert_near(a: Option<f32>, b: f32) {
+        let a = a.expect("expected a hit");
+        assert!((a - b).abs() < 0.0001, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_closest_on_segment() {
+        let (a, b) = (Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0));
+        assert_eq!(closest_on_segment(a, b, Vec2::new(4.0, 3.0)), Vec2::new(4.0, 0.0));
+        assert_eq!(closest_on_segment(a, b, Vec2::new(-4.0, 3.0)), a);
+        assert_eq!(closest_on_segment(a, b, Vec2::new(14.0, -3.0)), b);
+        assert_eq!(closest_on_segment(a, a, Vec2::new(4.0, 3.0)), a);
+    }
+
+    #[test]
+    fn test_ray_capsule() {
+        let (a, b) = (Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0));
+
+        // Starting inside
+        assert_near(ray_capsule(Vec2::new(5.0, 1.0), Vec2::new(0.0, 1.0), a, b, 2.0), 0.0);
+
+        // Straight sides
+        assert_near(ray_capsule(Vec2::new(5.0, -10.0), Vec2::new(0.0, 1.0), a, b, 2.0), 8.0);
+        assert_near(ray_capsule(Vec2::new(5.0, 10.0), Vec2::new(0.0, -1.0), a, b, 2.0), 8.0);
+
+        // Round caps
+        assert_near(ray_capsule(Vec2::new(-10.0, 0.0), Vec2::new(1.0, 0.0), a, b, 2.0), 8.0);
+        assert_near(ray_capsule(Vec2::new(11.0, -10.0), Vec2::new(0.0, 1.0), a, b, 2.0), 10.0 - 3.0f32.sqrt());
+
+        // Misses, including capsules behind the ray
+        assert_eq!(ray_capsule(Vec2::new(5.0, -10.0), Vec2::new(1.0, 0.0), a, b, 2.0), None);
+        assert_eq!(ray_capsule(Vec2::new(13.0, -10.0), Vec2::new(0.0, 1.0), a, b, 2.0), None);
+        assert_eq!(ray_capsule(Vec2::new(5.0, -10.0), Vec2::new(0.0, -1.0), a, b, 2.0), None);
+    }
+
+    fn hitbox_skeleton() -> Skeleton {
+        let mut skeleton = Skeleton::new(SkeletalData::from_ron(r#"(
+            bones: [
+                ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+                ("A", ("Root", 10.0, 0.0, 1.0, None, None)),
+                ("B", ("A", 10.0, 0.0, 1.0, None, None)),
+                ("C", ("B", 10.0, 0.0, 1.0, None, None)),
+            ],
+            hitboxes: [("A", 1.0), ("B", 2.0)]
+        )"#).unwrap());
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        skeleton
+    }
+
+    #[test]
+    fn test_hit_point() {
+        let skeleton = hitbox_skeleton();
+        let hit = skeleton.hit_point(Space::Local, Vec2::new(15.0, 1.5)).unwrap();
+        assert_eq!(hit.bone, skeleton.bone_id("B").unwrap());
+        assert_eq!(hit.point, Vec2::new(15.0, 0.0));
+        assert_eq!(hit.distance, 1.5);
+
+        // Bones without a hitbox are ignored
+        assert!(skeleton.hit_point(Space::Local, Vec2::new(25.0, 0.0)).is_none());
+        assert!(skeleton.hit_point(Space::Local, Vec2::new(5.0, 1.5)).is_none());
+    }
+
+    #[test]
+    fn test_hit_segment_and_ray() {
+        let skeleton = hitbox_skeleton();
+
+        // The first capsule along the segment is hit
+        let hit = skeleton.hit_segment(Space::Local, Vec2::new(30.0, 0.0), Vec2::new(0.0, 0.0)).unwrap();
+        assert_eq!(hit.bone, skeleton.bone_id("B").unwrap());
+        assert!((hit.point - Vec2::new(22.0, 0.0)).length() < 0.0001);
+        assert!((hit.distance - 8.0).abs() < 0.0001);
+
+        // Segments end before reaching a capsule, rays don't
+        assert!(skeleton.hit_segment(Space::Local, Vec2::new(5.0, -10.0), Vec2::new(5.0, -2.0)).is_none());
+        let hit = skeleton.hit_ray(Space::Local, Vec2::new(5.0, -10.0), Vec2::new(0.0, 1.0)).unwrap();
+        assert_eq!(hit.bone, skeleton.bone_id("A").unwrap());
+        assert!((hit.distance - 9.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_attachment_severed() {
+        let mut skeleton = Skeleton::new(SkeletalData::from_ron(r#"(
+            bones: [
+                ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+                ("A", ("Root", 10.0, 0.0, 1.0, None, None)),
+                ("B", ("A", 10.0, 0.0, 1.0, None, None)),
+            ],
+            sockets: [("Grip", ("B", 0.0, 1.0, 0.0))]
+        )"#).unwrap());
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+        assert!(!skeleton.is_attachment_severed("Grip"));
+
+        // Sockets and bone ends go along with any severed parent
+        assert!(skeleton.sever("A", None).unwrap().is_some());
+        assert!(skeleton.is_attachment_severed("Grip"));
+        assert!(skeleton.is_attachment_severed("B"));
+        assert!(!skeleton.is_attachment_severed("Root"));
+        assert!(!skeleton.is_attachment_severed("Unknown"));
+    }
+
+    #[test]
+    fn test_split_lengths() {
+        let mut skeleton = chain_skeleton();
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+
+        let mut ragdoll = skeleton.to_ragdoll().unwrap();
+        let (end, start) = ragdoll.constraint_points("B");
+        let cut = ragdoll.split_bone_at("B", 0.25).unwrap();
+        assert!((ragdoll.joint_position(cut) - (start + (end - start) * 0.25)).length() < 0.0001);
+
+        // The stump keeps the near part of the bone, the bone the far part
+        let (stump_end, stump_start) = ragdoll.constraint_points("B.Stump");
+        assert_eq!(stump_start, start);
+        assert!(((stump_end - stump_start).length() - 2.5).abs() < 0.0001);
+
+        let (bone_end, bone_start) = ragdoll.constraint_points("B");
+        assert_eq!(bone_end, end);
+        assert!(((bone_end - bone_start).length() - 7.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_stump_hitboxes() {
+        let mut skeleton = hitbox_skeleton();
+        assert!(skeleton.sever("B", Some(0.25)).unwrap().is_some());
+        skeleton.step(0.016, Vec2::zero(), |_| {});
+
+        // Only the stump of the severed bone can still be hit
+        let b = skeleton.bone_id("B").unwrap();
+        let hit = skeleton.hit_point(Space::Local, Vec2::new(12.0, 1.5)).unwrap();
+        assert_eq!(hit.bone, b);
+        assert!(skeleton.hit_point(Space::Local, Vec2::new(16.0, 1.5)).is_none());
+
+        // Also when the stump is part of the ragdoll
+        skeleton.start_ragdoll().unwrap();
+        let hit = skeleton.hit_point(Space::Local, Vec2::new(12.0, 1.5)).unwrap();
+        assert_eq!(hit.bone, b);
+        assert!(skeleton.hit_point(Space::Local, Vec2::new(16.0, 1.5)).is_none());
+
+        // Stumps go along with their severed parents
+        assert!(skeleton.sever("A", None).unwrap().is_some());
+        assert!(skeleton.hit_point(Space::Local, Vec2::new(12.0, 1.5)).is_none());
+    }
+
+    #[test]
+    fn test_teleport_resets_velocities() {
+        let mut skeleton = chain_skeleton();
+        let c = skeleton.bone_id("C").unwrap();
+        for i in 0..4 {
+            skeleton.set_world_offset(Vec2::new(i as f32, 0.0));
+            skeleton.step(0.5, Vec2::zero(), |_| {});
+        }
+        assert_eq!(skeleton.bone_velocity_by_id(c), Vec2::new(2.0, 0.0));
+
+        skeleton.set_world_offset(Vec2::new(500.0, 0.0));
+        assert_eq!(skeleton.bone_velocity_by_id(c), Vec2::zero());
+        skeleton.step(0.5, Vec2::zero(), |_| {});
+        assert_eq!(skeleton.bone_velocity_by_id(c), Vec2::zero());
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        let bones = |limits: &str| format!(r#"(bones: [
+            ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+            ("A", ("Root", 10.0, 0.0, 1.0, {})),
+        ])"#, limits);
+        assert!(validate(&bones("Some(-3.0), Some(3.0)")).is_ok());
+        assert!(validate(&bones("None, Some(-1.0)")).is_ok());
+        for limits in &["Some(1.0), Some(-1.0)", "Some(-4.0), None", "None, Some(4.0)"] {
+            match validate(&bones(limits)) {
+                Err(SkeletonError::InvalidLimits(bone)) => assert_eq!(bone, "A"),
+                r => panic!("expected invalid limits for {}, got {:?}", limits, r)
             }
+        }
+    }
+
+    #[test]
+    fn test_sever_keeps_ragdoll_splits() {
+        let skeleton = || {
+            let mut skeleton = Skeleton::new(SkeletalData::from_ron(r#"(
+                bones: [
+                    ("Root", ("Root", 0.0, 0.0, 1.0, None, None)),
+                    ("A", ("Root", 10.0, 0.0, 1.0, None, None)),
+                    ("B", ("A", 10.0, 0.0, 1.0, None, None)),
+                    ("C", ("B", 10.0, 0.0, 1.0, None, None)),
+                ],
+                ragdoll_splits: [("B", Some((3.1416, 0.7854)))]
+            )"#).unwrap());
+            skeleton.step(0.016, Vec2::zero(), |_| {});
+            skeleton
+        };
+
+        let limits = |piece: &Ragdoll| {
+            let mut count = 0;
+            piece.visit_helpers(|_, _, typ| if typ == ConstraintType::Angular {
+                count += 1;
+            });
+            count
+        };
+
+        // Severed while animated...
+        let animated = skeleton().sever("B", None).unwrap().unwrap();
+
+        // ...and during the ragdoll
+        let mut ragdolled = skeleton();
+        ragdolled.start_ragdoll().unwrap();
+        let ragdolled = ragdolled.sever("B", None).unwrap().unwrap();
 
+        assert!(limits(&ragdolled) > 0);
+        assert_eq!(limits(&animated), limits(&ragdolled));
+        for bone in &["B", "C"] {
+            let (a, b) = (animated.constraint_points(bone), ragdolled.constraint_points(bone));
+            assert!((a.0 - b.0).length() < 0.0001 && (a.1 - b.1).length() < 0.0001);
         }
+    }
+
+    #[test]
+    fn test_fabrik_converges_and_keeps_lengths() {
+        let lengths = [10.0, 5.0, 8.0];
+        let mut joints = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(15.0, 0.0),
+            Vec2::new(23.0, 0.0)
+        ];
+        let target = Vec2::new(-4.0, 14.0);
+        assert!(solve_chain_ik(&mut joints, &lengths, target, 20, 0.01));
+        assert!((joints[3] - target).length() <= 0.01);
+        assert_eq!(joints[0], Vec2::new(0.0, 0.0));
+        for (i, length) in lengths.iter().enumerate() {
+            assert!(((joints[i + 1] - joints[i]).length() - length).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_fabrik_extends_towards_unreachable_target() {
+        let lengths = [10.0, 10.0];
+        let mut joints = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)];
+        assert!(!solve_chain_ik(&mut joints, &lengths, Vec2::new(0.0, 50.0), 20, 0.01));
+        assert!((joints[1] - Vec2::new(0.0, 10.0)).length() < 0.001);
+        assert!((joints[2] - Vec2::new(0.0, 20.0)).length() < 0.001);
+    }
+
+    #[test]
+    fn test_fabrik_respects_limits() {
+        let a = chain_skeleton().bone_id("A").unwrap();
+        let limits_ok = |skeleton: &Skeleton| {
+            let angle = skeleton.bones[a.0].angle - skeleton.parent_angle(a.0);
+            angle.abs() <= 0.6 + 0.0001
+        };
 
-        // Forward phase
-        for i in bone_list.iter().rev() {
-            let start = bones[bones[*i].parent].end_computed();
-            bones[*i].set_start_ik(start);
+        // Closest point within the root bone's limits, instead of the
+        // clamped unlimited solution
+        let target = Vec2::new(0.0, 25.0);
+        let best = (target - Vec2::new(0.6f32.cos(), 0.6f32.sin()) * 10.0).length() - 20.0;
+        for offset in &[0.0, 0.5, -0.5] {
+            let mut skeleton = chain_skeleton();
+            skeleton.apply_bone_angle("A", *offset);
+            skeleton.step(0.016, Vec2::zero(), |_| {});
+            let solver = IKSolver::FABRIK { iterations: 50, tolerance: 0.01 };
+            assert!(!skeleton.apply_chain_ik("C", "A", target, solver, false).unwrap());
+
+            let tip = skeleton.bone_end(Space::Animation, "C").unwrap();
+            assert!((tip - target).length() < best + 0.05, "{:?} is too far from {:?}", tip, target);
+            assert!(limits_ok(&skeleton));
         }
 
+        // Reachable targets are still reached
+        let (reached, tip) = solve_chain(IKSolver::FABRIK { iterations: 50, tolerance: 0.01 }, 0.0, Vec2::new(5.0, 20.0));
+        assert!(reached);
+        assert!((tip - Vec2::new(5.0, 20.0)).length() <= 0.01);
     }
 
+    #[test]
+    fn test_chain_ik_unknown_bones() {
+        let mut skeleton = chain_skeleton();
+        let solver = IKSolver::FABRIK { iterations: 20, tolerance: 0.01 };
+        match skeleton.apply_chain_ik("Foo", "A", Vec2::zero(), solver, false) {
+            Err(SkeletonError::UnknownBone(name)) => assert_eq!(name, "Foo"),
+            _ => panic!("expected an unknown bone error")
+        }
+    }
+
+    #[test]
+    fn test_ccd_matches_fabrik_with_offset_bones() {
+        let target = Vec2::new(20.0, 12.0);
+        for offset in &[0.0, 0.5, -0.5] {
+            let fabrik = solve_chain(IKSolver::FABRIK { iterations: 20, tolerance: 0.01 }, *offset, target);
+            let ccd = solve_chain(IKSolver::CCD { iterations: 50, tolerance: 0.01 }, *offset, target);
+            assert!(fabrik.0 && ccd.0);
+            assert!((fabrik.1 - target).length() < 0.05);
+            assert!((ccd.1 - target).length() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_ccd_respects_limits_with_offset_bones() {
+        // Out of reach with the root bone's limits in place
+        let target = Vec2::new(0.0, 25.0);
+        for offset in &[0.0, 0.5, -0.5] {
+            let (reached, tip) = solve_chain(IKSolver::CCD { iterations: 50, tolerance: 0.01 }, *offset, target);
+            assert!(!reached);
+            assert!((tip - target).length() > 1.0);
+        }
+    }
 }
-*/
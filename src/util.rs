@@ -59,6 +59,10 @@ impl Vec2 {
         }
     }
 
+    pub fn dot(&self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
     pub fn cross(&self, other: Vec2) -> Vec2 {
         Vec2 {
             x: self.x * other.y - other.x * self.y,
@@ -159,6 +163,54 @@ impl Div<f32> for Vec2 {
 }
 
 
+// 2D Transform Abstraction ---------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vec2,
+    pub rotation: f32,
+    pub scale: f32
+}
+
+impl Transform {
+
+    pub fn new(position: Vec2, rotation: f32, scale: f32) -> Self {
+        Self {
+            position,
+            rotation,
+            scale
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec2::zero(), 0.0, 1.0)
+    }
+
+    pub fn apply(&self, p: Vec2) -> Vec2 {
+        self.apply_vector(p) + self.position
+    }
+
+    pub fn apply_inverse(&self, p: Vec2) -> Vec2 {
+        self.apply_inverse_vector(p - self.position)
+    }
+
+    // Vectors are only rotated and scaled, but not translated
+    pub fn apply_vector(&self, v: Vec2) -> Vec2 {
+        (v * self.scale).rotate(self.rotation)
+    }
+
+    pub fn apply_inverse_vector(&self, v: Vec2) -> Vec2 {
+        v.rotate(-self.rotation) / self.scale
+    }
+
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+
 // Angle Abstraction ----------------------------------------------------------
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Facing {
@@ -223,6 +275,12 @@ impl Angle {
 
     }
 
+    // Interpolates along the shortest arc
+    pub fn lerp(from: f32, to: f32, t: f32) -> f32 {
+        let r = to - from;
+        from + r.sin().atan2(r.cos()) * t
+    }
+
     pub fn offset(r: f32, distance: f32) -> Vec2 {
         Angle::from_radians(r).to_vec(distance)
     }